@@ -4,6 +4,7 @@
 
 #![forbid(unsafe_code)]
 
+use std::collections::HashMap;
 use std::time::Duration;
 
 /// Dimensionless risk coordinate r_x ∈ [0,1], plus metadata.
@@ -29,6 +30,10 @@ pub struct Residual {
 
 impl Residual {
     /// Recompute V_t = Σ w_j * r_j from internal rx.[file:18]
+    ///
+    /// Linear form only. For a corridor that declares another residual form
+    /// (quadratic, max-norm, log-barrier), compute V_t with that form on the
+    /// corridor side and set `vt` directly instead of calling this.
     pub fn recompute(&mut self) {
         let mut v = 0.0;
         for (j, rc) in self.rx.iter().enumerate() {
@@ -74,6 +79,206 @@ pub fn enforce_safestep(prev: &Residual, next: &Residual) -> CorridorDecision {
     }
 }
 
+/// Tuning for SafestepEngine; keeps noisy residuals from chattering the line
+/// between derate and normal.
+#[derive(Clone, Debug)]
+pub struct SafestepConfig {
+    pub epsilon: f64,               // tolerated V_t rise per step
+    pub band: f64,                  // hysteresis half-width around safe/gold/hard, in r units
+    pub min_derate_dwell: Duration, // minimum time spent in derate once entered
+    pub recovery_steps: u32,        // consecutive V_t decreases required to leave derate
+}
+
+impl Default for SafestepConfig {
+    fn default() -> Self {
+        SafestepConfig {
+            epsilon: 1e-3,
+            band: 0.02,
+            min_derate_dwell: Duration::from_secs(15 * 60),
+            recovery_steps: 3,
+        }
+    }
+}
+
+/// Band a risk coordinate sits in: r ≤ safe, ≤ gold, < hard, ≥ hard.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum CorridorZone {
+    Safe,
+    Gold,
+    Elevated,
+    Hard,
+}
+
+impl CorridorZone {
+    fn of(rc: &RiskCoord, value: f64) -> Self {
+        if value >= rc.hard {
+            CorridorZone::Hard
+        } else if value > rc.gold {
+            CorridorZone::Elevated
+        } else if value > rc.safe {
+            CorridorZone::Gold
+        } else {
+            CorridorZone::Safe
+        }
+    }
+
+    /// Escalate as soon as a threshold is crossed; fall back only once the
+    /// value is a full band below it.
+    fn update(prev: CorridorZone, rc: &RiskCoord, band: f64) -> Self {
+        let up = CorridorZone::of(rc, rc.value);
+        if up >= prev {
+            up
+        } else {
+            prev.min(CorridorZone::of(rc, rc.value + band))
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SafestepMode {
+    Normal,
+    Derate,
+    Stop,
+}
+
+/// Stateful version of enforce_safestep with ε, hysteresis bands, a derate
+/// dwell time and an explicit recovery criterion.
+///
+/// - Any coordinate in the hard zone, or at r ≥ 1.0 whatever its configured
+///   hard threshold, stops the line. Once every coordinate is a band below
+///   hard again, the line stays derated and has to recover.
+/// - A non-finite V_t or coordinate value cannot be judged and stops the line
+///   the same way.
+/// - V_t may rise by up to ε per step. A larger rise derates the line, unless
+///   every coordinate is still in the safe zone.
+/// - Derate ends once min_derate_dwell has elapsed since the last trigger, V_t
+///   has decreased on recovery_steps consecutive steps and no coordinate is
+///   above gold.
+///
+/// The engine only compares `vt` between steps, so it works with any residual
+/// form as long as prev and next come from the form the corridor declares;
+/// `Residual::recompute` is linear-only. ε is in units of that form's V_t.
+#[derive(Clone, Debug)]
+pub struct SafestepEngine {
+    pub cfg: SafestepConfig,
+    mode: SafestepMode,
+    zones: HashMap<String, CorridorZone>,
+    derate_since: Duration,
+    decreasing_steps: u32,
+}
+
+impl SafestepEngine {
+    pub fn new(cfg: SafestepConfig) -> Self {
+        SafestepEngine {
+            cfg,
+            mode: SafestepMode::Normal,
+            zones: HashMap::new(),
+            derate_since: Duration::ZERO,
+            decreasing_steps: 0,
+        }
+    }
+
+    pub fn mode(&self) -> SafestepMode {
+        self.mode
+    }
+
+    /// Zone of var_id after the last step, with hysteresis applied.
+    pub fn zone(&self, var_id: &str) -> Option<CorridorZone> {
+        self.zones.get(var_id).copied()
+    }
+
+    /// Judge the step prev -> next observed at `now` (monotonic time).
+    pub fn step(&mut self, prev: &Residual, next: &Residual, now: Duration) -> CorridorDecision {
+        // NaN fails every comparison below, which would read as "no rise".
+        let finite = prev.vt.is_finite()
+            && next.vt.is_finite()
+            && next.rx.iter().all(|rc| rc.value.is_finite());
+        if !finite {
+            self.mode = SafestepMode::Stop;
+            self.decreasing_steps = 0;
+            return self.decision("non-finite residual or risk coordinate".to_string());
+        }
+
+        let mut worst = CorridorZone::Safe;
+        for rc in &next.rx {
+            let prev_zone = self
+                .zones
+                .get(&rc.var_id)
+                .copied()
+                .unwrap_or(CorridorZone::Safe);
+            let zone = CorridorZone::update(prev_zone, rc, self.cfg.band);
+            self.zones.insert(rc.var_id.clone(), zone);
+            worst = worst.max(zone);
+        }
+
+        let at_limit = next.rx.iter().any(|rc| rc.value >= 1.0);
+        if worst == CorridorZone::Hard || at_limit {
+            self.mode = SafestepMode::Stop;
+            self.decreasing_steps = 0;
+            return self.decision("hard corridor limit exceeded".to_string());
+        }
+        if self.mode == SafestepMode::Stop {
+            // Hard zone cleared: restart the dwell and require a full recovery.
+            self.enter_derate(now);
+            return self.decision("hard limit cleared; derated pending recovery".to_string());
+        }
+
+        let rise = next.vt - prev.vt;
+        if rise > self.cfg.epsilon && worst > CorridorZone::Safe {
+            self.enter_derate(now);
+            return self.decision(format!(
+                "Lyapunov residual increased by {:.4} (epsilon {:.4})",
+                rise, self.cfg.epsilon
+            ));
+        }
+
+        if self.mode == SafestepMode::Normal {
+            return self.decision("within corridors".to_string());
+        }
+
+        if rise < 0.0 {
+            self.decreasing_steps += 1;
+        } else {
+            self.decreasing_steps = 0;
+        }
+        let dwelt = now.saturating_sub(self.derate_since);
+        if dwelt < self.cfg.min_derate_dwell {
+            return self.decision(format!(
+                "derate dwell {}s of {}s",
+                dwelt.as_secs(),
+                self.cfg.min_derate_dwell.as_secs()
+            ));
+        }
+        if self.decreasing_steps < self.cfg.recovery_steps {
+            return self.decision(format!(
+                "recovering: {} of {} decreasing steps",
+                self.decreasing_steps, self.cfg.recovery_steps
+            ));
+        }
+        if worst > CorridorZone::Gold {
+            return self.decision("recovering: a coordinate is still above gold".to_string());
+        }
+
+        self.mode = SafestepMode::Normal;
+        self.decreasing_steps = 0;
+        self.decision("recovered; back within corridors".to_string())
+    }
+
+    fn enter_derate(&mut self, now: Duration) {
+        self.mode = SafestepMode::Derate;
+        self.derate_since = now;
+        self.decreasing_steps = 0;
+    }
+
+    fn decision(&self, reason: String) -> CorridorDecision {
+        CorridorDecision {
+            derate: self.mode != SafestepMode::Normal,
+            stop: self.mode == SafestepMode::Stop,
+            reason,
+        }
+    }
+}
+
 /// RegionConfig: container for all Phoenix/region-specific parameters.[file:30][file:20]
 pub trait RegionConfig {
     fn region_code(&self) -> &str;
@@ -325,7 +530,7 @@ fn estimate_t90_days_from_mix<E: EnvFeed>(mix: &TrayMaterialMix, env: &E) -> f64
 
     // Baseline k at 25 °C for starch-rich blends ~0.05 d⁻¹, adjusted via Q10.[file:30]
     let k_base = 0.05;
-    let q10 = 2.0;
+    let q10: f64 = 2.0;
     let delta_t = temp - 25.0;
     let k = k_base * q10.powf(delta_t / 10.0);
 
@@ -343,7 +548,6 @@ fn estimate_rtox_from_mix(mix: &TrayMaterialMix, region: &dyn RegionConfig) -> f
         + 0.05 * mix.mineral_frac
         + 0.01 * mix.protein_frac.max(0.0);
     let safe = region.rtox_safe();
-    let gold = region.rtox_gold();
     let hard = region.rtox_hard();
 
     if base <= safe {
@@ -372,7 +576,7 @@ impl<E: EnvFeed> EcoScorable for PhoenixTrayCandidate<E> {
         let r_tox = estimate_rtox_from_mix(&self.mix, region);
 
         // Primary gates: t90 ≤ hard limit, r_tox ≤ 0.1 corridor.[file:30]
-        let mut risk_of_harm = 0.0;
+        let mut risk_of_harm: f64 = 0.0;
         if modeled_t90 > region.t90_hard_limit_days() {
             risk_of_harm = 1.0;
         }
@@ -529,3 +733,177 @@ pub fn qpu_tray_shard_to_csv(rows: &[QpuTrayShardRow]) -> String {
     }
     out
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAFE: f64 = 0.3;
+    const GOLD: f64 = 0.6;
+    const HARD: f64 = 0.9;
+
+    fn residual(vt: f64, value: f64) -> Residual {
+        Residual {
+            vt,
+            weights: vec![1.0],
+            rx: vec![RiskCoord {
+                var_id: "r_tox".to_string(),
+                value,
+                safe: SAFE,
+                gold: GOLD,
+                hard: HARD,
+                weight: 1.0,
+                lyap_channel: 0,
+            }],
+        }
+    }
+
+    fn engine() -> SafestepEngine {
+        SafestepEngine::new(SafestepConfig {
+            epsilon: 0.01,
+            band: 0.05,
+            min_derate_dwell: Duration::from_secs(60),
+            recovery_steps: 3,
+        })
+    }
+
+    fn secs(s: u64) -> Duration {
+        Duration::from_secs(s)
+    }
+
+    /// Derate at t = 0 with r_tox in the gold zone.
+    fn derated() -> SafestepEngine {
+        let mut e = engine();
+        let d = e.step(&residual(0.5, 0.5), &residual(0.52, 0.5), secs(0));
+        assert!(d.derate && !d.stop);
+        assert_eq!(e.mode(), SafestepMode::Derate);
+        e
+    }
+
+    #[test]
+    fn test_rise_within_epsilon_is_tolerated() {
+        let mut e = engine();
+        let d = e.step(&residual(0.5, 0.5), &residual(0.509, 0.5), secs(0));
+        assert!(!d.derate);
+        assert_eq!(e.mode(), SafestepMode::Normal);
+
+        let d = e.step(&residual(0.509, 0.5), &residual(0.53, 0.5), secs(1));
+        assert!(d.derate && !d.stop);
+
+        // In the safe zone a rise above epsilon does not derate.
+        let mut e = engine();
+        let d = e.step(&residual(0.1, 0.1), &residual(0.2, 0.2), secs(0));
+        assert!(!d.derate);
+    }
+
+    #[test]
+    fn test_zone_falls_back_only_a_band_below_threshold() {
+        let mut e = engine();
+        let mut step = |value: f64| {
+            e.step(&residual(0.5, value), &residual(0.5, value), secs(0));
+            e.zone("r_tox").unwrap()
+        };
+        assert_eq!(step(0.65), CorridorZone::Elevated);
+        // Below gold, but not by a full band.
+        assert_eq!(step(0.58), CorridorZone::Elevated);
+        assert_eq!(step(0.56), CorridorZone::Elevated);
+        assert_eq!(step(0.54), CorridorZone::Gold);
+        // Escalation is immediate.
+        assert_eq!(step(0.61), CorridorZone::Elevated);
+    }
+
+    #[test]
+    fn test_dwell_blocks_recovery() {
+        let mut e = derated();
+        let mut vt = 0.52;
+        for t in [10, 20, 30, 40, 50] {
+            let d = e.step(&residual(vt, 0.5), &residual(vt - 0.01, 0.5), secs(t));
+            vt -= 0.01;
+            assert!(d.derate, "left derate at {}s", t);
+            assert!(d.reason.starts_with("derate dwell"), "{}", d.reason);
+        }
+        let d = e.step(&residual(vt, 0.5), &residual(vt - 0.01, 0.5), secs(60));
+        assert!(!d.derate, "{}", d.reason);
+        assert_eq!(e.mode(), SafestepMode::Normal);
+    }
+
+    #[test]
+    fn test_non_decrease_resets_recovery_count() {
+        let mut e = derated();
+        let mut vt = 0.52;
+        let mut step = |e: &mut SafestepEngine, dv: f64, t: u64| {
+            let d = e.step(&residual(vt, 0.5), &residual(vt + dv, 0.5), secs(t));
+            vt += dv;
+            d
+        };
+        assert!(step(&mut e, -0.01, 100).derate);
+        assert!(step(&mut e, -0.01, 110).derate);
+        // Flat, not decreasing: the count starts over.
+        let d = step(&mut e, 0.0, 120);
+        assert_eq!(d.reason, "recovering: 0 of 3 decreasing steps");
+        assert!(step(&mut e, -0.01, 130).derate);
+        let d = step(&mut e, -0.01, 140);
+        assert_eq!(d.reason, "recovering: 2 of 3 decreasing steps");
+        assert!(!step(&mut e, -0.01, 150).derate);
+    }
+
+    #[test]
+    fn test_hard_clear_drops_to_derate_not_normal() {
+        let mut e = engine();
+        let d = e.step(&residual(0.5, 0.5), &residual(0.95, 0.95), secs(0));
+        assert!(d.stop);
+        // Below hard, but within a band of it: still stopped.
+        let d = e.step(&residual(0.95, 0.95), &residual(0.87, 0.87), secs(10));
+        assert!(d.stop);
+        assert_eq!(e.zone("r_tox"), Some(CorridorZone::Hard));
+
+        let d = e.step(&residual(0.87, 0.87), &residual(0.5, 0.5), secs(20));
+        assert!(d.derate && !d.stop);
+        assert_eq!(e.mode(), SafestepMode::Derate);
+
+        // The dwell restarts at the clear, not at the original stop.
+        let mut vt = 0.5;
+        for t in [30, 40, 50, 60, 70] {
+            let d = e.step(&residual(vt, 0.5), &residual(vt - 0.01, 0.5), secs(t));
+            vt -= 0.01;
+            assert!(d.derate && !d.stop, "at {}s: {}", t, d.reason);
+        }
+        let d = e.step(&residual(vt, 0.5), &residual(vt - 0.01, 0.5), secs(80));
+        assert!(!d.derate, "{}", d.reason);
+    }
+
+    #[test]
+    fn test_value_at_one_stops_whatever_the_hard_threshold() {
+        let mut e = engine();
+        let mut next = residual(1.0, 1.0);
+        next.rx[0].hard = 1.5;
+        let d = e.step(&residual(0.5, 0.5), &next, secs(0));
+        assert!(d.stop);
+        assert_eq!(e.mode(), SafestepMode::Stop);
+        assert_eq!(e.zone("r_tox"), Some(CorridorZone::Elevated));
+    }
+
+    #[test]
+    fn test_non_finite_values_stop() {
+        for (prev_vt, vt, value) in [
+            (0.5, f64::NAN, 0.5),
+            (f64::NAN, 0.5, 0.5),
+            (0.5, f64::INFINITY, 0.5),
+            (0.5, 0.5, f64::NAN),
+            (0.5, 0.5, f64::NEG_INFINITY),
+        ] {
+            let mut e = engine();
+            let d = e.step(&residual(prev_vt, 0.5), &residual(vt, value), secs(0));
+            assert!(d.stop, "{} -> {} with r = {}", prev_vt, vt, value);
+            assert_eq!(d.reason, "non-finite residual or risk coordinate");
+        }
+
+        // NaN must not count as a decreasing step during recovery either.
+        let mut e = derated();
+        let d = e.step(&residual(0.52, 0.5), &residual(f64::NAN, 0.5), secs(100));
+        assert!(d.stop);
+        let d = e.step(&residual(0.5, 0.5), &residual(0.49, 0.5), secs(110));
+        assert!(d.derate && !d.stop, "{}", d.reason);
+        assert_eq!(e.mode(), SafestepMode::Derate);
+    }
+}
@@ -0,0 +1,56 @@
+//! Compiles every CONTRACT in `spec/*.aln` into `$OUT_DIR/aln_contracts.rs`,
+//! which `src/contracts.rs` includes. A spec that does not parse fails the
+//! build with the parser's diagnostic.
+
+use std::collections::BTreeSet;
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+#[allow(dead_code, unused_imports)]
+#[path = "src/aln/mod.rs"]
+mod aln;
+
+fn main() {
+    let manifest_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR").expect("CARGO_MANIFEST_DIR"));
+    let out_dir = PathBuf::from(env::var("OUT_DIR").expect("OUT_DIR"));
+    let spec_dir = manifest_dir.join("..").join("spec");
+
+    println!("cargo:rerun-if-changed={}", spec_dir.display());
+    println!("cargo:rerun-if-changed=src/aln");
+
+    let mut specs: Vec<PathBuf> = fs::read_dir(&spec_dir)
+        .unwrap_or_else(|e| panic!("cannot read {}: {}", spec_dir.display(), e))
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|p| p.extension().is_some_and(|ext| ext == "aln"))
+        .collect();
+    specs.sort();
+
+    let mut generated = String::from("// @generated by build.rs from spec/*.aln; do not edit.\n\n");
+    let mut contracts = BTreeSet::new();
+    for path in &specs {
+        println!("cargo:rerun-if-changed={}", path.display());
+        let origin = format!(
+            "spec/{}",
+            path.file_name().unwrap_or_default().to_string_lossy()
+        );
+        let src = fs::read_to_string(path)
+            .unwrap_or_else(|e| panic!("cannot read {}: {}", path.display(), e));
+        let file = aln::parse_aln(&src).unwrap_or_else(|e| panic!("\n{}", e.render(&src, &origin)));
+        for c in &file.contracts {
+            if !contracts.insert(c.name.name.clone()) {
+                panic!(
+                    "contract `{}` in {} is defined in another spec file",
+                    c.name.name, origin
+                );
+            }
+        }
+        let code = aln::codegen::generate_rust(&file, &src, &origin)
+            .unwrap_or_else(|e| panic!("\n{}", e.render(&src, &origin)));
+        generated.push_str(&code);
+    }
+
+    let target = out_dir.join("aln_contracts.rs");
+    fs::write(&target, generated)
+        .unwrap_or_else(|e| panic!("cannot write {}: {}", target.display(), e));
+}
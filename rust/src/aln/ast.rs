@@ -0,0 +1,249 @@
+use std::fmt;
+
+/// Byte range into the `.aln` source.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Span {
+    pub fn new(start: usize, end: usize) -> Self {
+        Span { start, end }
+    }
+
+    /// Smallest span covering both.
+    pub fn to(self, other: Span) -> Span {
+        Span {
+            start: self.start.min(other.start),
+            end: self.end.max(other.end),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Ident {
+    pub name: String,
+    pub span: Span,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AlnType {
+    Real,
+    Int,
+    Bool,
+}
+
+impl AlnType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AlnType::Real => "REAL",
+            AlnType::Int => "INT",
+            AlnType::Bool => "BOOL",
+        }
+    }
+
+    pub fn is_numeric(&self) -> bool {
+        !matches!(self, AlnType::Bool)
+    }
+}
+
+impl fmt::Display for AlnType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// `INPUT name[SIZE] : TYPE;` or `OUTPUT name : TYPE;`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Decl {
+    pub name: Ident,
+    pub size: Option<Expr>,
+    pub ty: AlnType,
+    pub span: Span,
+}
+
+/// `CONST NAME = expr;`
+#[derive(Clone, Debug, PartialEq)]
+pub struct ConstDef {
+    pub name: Ident,
+    pub value: Expr,
+    pub span: Span,
+}
+
+/// `EXTERN NAME;`: a numeric constant the caller binds.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ExternDef {
+    pub name: Ident,
+    pub span: Span,
+}
+
+/// `LET name = expr;`
+#[derive(Clone, Debug, PartialEq)]
+pub struct Let {
+    pub name: Ident,
+    pub value: Expr,
+    pub span: Span,
+}
+
+/// `INVARIANT Name: expr;`
+#[derive(Clone, Debug, PartialEq)]
+pub struct Invariant {
+    pub name: Ident,
+    pub expr: Expr,
+    pub span: Span,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Contract {
+    pub name: Ident,
+    pub consts: Vec<ConstDef>,
+    /// Constants bound by the caller (e.g. NUM_CHANNELS, EPS), in
+    /// declaration order.
+    pub externs: Vec<ExternDef>,
+    pub inputs: Vec<Decl>,
+    pub outputs: Vec<Decl>,
+    pub lets: Vec<Let>,
+    pub invariants: Vec<Invariant>,
+    pub span: Span,
+}
+
+impl Contract {
+    pub fn input(&self, name: &str) -> Option<&Decl> {
+        self.inputs.iter().find(|d| d.name.name == name)
+    }
+
+    pub fn output(&self, name: &str) -> Option<&Decl> {
+        self.outputs.iter().find(|d| d.name.name == name)
+    }
+}
+
+/// A parsed `.aln` file.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AlnFile {
+    pub contracts: Vec<Contract>,
+}
+
+impl AlnFile {
+    pub fn contract(&self, name: &str) -> Option<&Contract> {
+        self.contracts.iter().find(|c| c.name.name == name)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UnaryOp {
+    Neg,
+    Not,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BinaryOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Eq,
+    Ne,
+    And,
+    Or,
+}
+
+impl BinaryOp {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BinaryOp::Add => "+",
+            BinaryOp::Sub => "-",
+            BinaryOp::Mul => "*",
+            BinaryOp::Div => "/",
+            BinaryOp::Lt => "<",
+            BinaryOp::Le => "<=",
+            BinaryOp::Gt => ">",
+            BinaryOp::Ge => ">=",
+            BinaryOp::Eq => "==",
+            BinaryOp::Ne => "!=",
+            BinaryOp::And => "AND",
+            BinaryOp::Or => "OR",
+        }
+    }
+
+    pub fn is_comparison(&self) -> bool {
+        matches!(
+            self,
+            BinaryOp::Lt | BinaryOp::Le | BinaryOp::Gt | BinaryOp::Ge | BinaryOp::Eq | BinaryOp::Ne
+        )
+    }
+
+    pub fn is_logical(&self) -> bool {
+        matches!(self, BinaryOp::And | BinaryOp::Or)
+    }
+}
+
+/// Built-in functions callable from `.aln` expressions.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Builtin {
+    Abs,
+    Min,
+    Max,
+    Clip01,
+}
+
+impl Builtin {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "ABS" => Some(Builtin::Abs),
+            "MIN" => Some(Builtin::Min),
+            "MAX" => Some(Builtin::Max),
+            "CLIP01" => Some(Builtin::Clip01),
+            _ => None,
+        }
+    }
+
+    pub fn arity(&self) -> usize {
+        match self {
+            Builtin::Abs | Builtin::Clip01 => 1,
+            Builtin::Min | Builtin::Max => 2,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Expr {
+    pub kind: ExprKind,
+    pub span: Span,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ExprKind {
+    Number(f64),
+    Bool(bool),
+    Var(String),
+    /// `name[index]`; only declared arrays can be indexed.
+    Index {
+        array: Ident,
+        index: Box<Expr>,
+    },
+    Unary {
+        op: UnaryOp,
+        operand: Box<Expr>,
+    },
+    Binary {
+        op: BinaryOp,
+        lhs: Box<Expr>,
+        rhs: Box<Expr>,
+    },
+    /// `SUM(var IN lo..hi) (body)`; both bounds are inclusive.
+    Sum {
+        var: Ident,
+        lo: Box<Expr>,
+        hi: Box<Expr>,
+        body: Box<Expr>,
+    },
+    Call {
+        func: Builtin,
+        args: Vec<Expr>,
+    },
+}
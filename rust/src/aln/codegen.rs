@@ -0,0 +1,495 @@
+use super::ast::*;
+use super::AlnError;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+
+const RUST_KEYWORDS: &[&str] = &[
+    "as", "break", "const", "continue", "crate", "else", "enum", "extern", "false", "fn", "for",
+    "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub", "ref", "return",
+    "self", "static", "struct", "super", "trait", "true", "type", "unsafe", "use", "where",
+    "while", "async", "await", "dyn",
+];
+
+/// `NonIncreasingResidual` → `non_increasing_residual`, `V_prev` → `v_prev`.
+pub fn snake_case(name: &str) -> String {
+    let mut out = String::new();
+    let mut prev: Option<char> = None;
+    for c in name.chars() {
+        if c.is_ascii_uppercase()
+            && prev.is_some_and(|p| p.is_ascii_lowercase() || p.is_ascii_digit())
+        {
+            out.push('_');
+        }
+        out.push(c.to_ascii_lowercase());
+        prev = Some(c);
+    }
+    if RUST_KEYWORDS.contains(&out.as_str()) {
+        out.push('_');
+    }
+    out
+}
+
+fn rust_type(ty: AlnType) -> &'static str {
+    match ty {
+        AlnType::Real => "f64",
+        AlnType::Int => "i64",
+        AlnType::Bool => "bool",
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Name {
+    External,
+    Const,
+    Input(AlnType),
+    InputArray(AlnType),
+    Let { is_bool: bool },
+}
+
+/// Emits one contract module. Generated expressions compute in f64 (INT
+/// values are widened), and fallible operations go through the helpers of
+/// `crate::contracts` (`at`, `div`, `int`, `sum`, `check_len`).
+struct Gen<'a> {
+    src: &'a str,
+    contract: &'a Contract,
+    names: BTreeMap<String, Name>,
+    sum_vars: Vec<String>,
+}
+
+impl Gen<'_> {
+    fn error<T>(&self, span: Span, message: String) -> Result<T, AlnError> {
+        Err(AlnError::new(self.src, span, message))
+    }
+
+    fn is_bool(&self, e: &Expr) -> bool {
+        match &e.kind {
+            ExprKind::Bool(_) => true,
+            ExprKind::Var(name) => match self.names.get(name) {
+                Some(Name::Input(ty)) => *ty == AlnType::Bool,
+                Some(Name::Let { is_bool }) => *is_bool,
+                _ => false,
+            },
+            ExprKind::Index { array, .. } => {
+                matches!(
+                    self.names.get(&array.name),
+                    Some(Name::InputArray(AlnType::Bool))
+                )
+            }
+            ExprKind::Unary { op, .. } => *op == UnaryOp::Not,
+            ExprKind::Binary { op, .. } => op.is_comparison() || op.is_logical(),
+            ExprKind::Number(_) | ExprKind::Sum { .. } | ExprKind::Call { .. } => false,
+        }
+    }
+
+    fn expr(&mut self, e: &Expr) -> Result<String, AlnError> {
+        Ok(match &e.kind {
+            ExprKind::Number(x) => {
+                if !x.is_finite() {
+                    return self.error(e.span, format!("literal {} is not finite", x));
+                }
+                format!("{:?}_f64", x)
+            }
+            ExprKind::Bool(b) => b.to_string(),
+            ExprKind::Var(name) => {
+                if self.sum_vars.contains(name) {
+                    return Ok(format!("s_{}", snake_case(name)));
+                }
+                let field = snake_case(name);
+                match self.names.get(name) {
+                    Some(Name::External) => format!("k.{}", field),
+                    Some(Name::Const) => format!("c_{}", field),
+                    Some(Name::Input(AlnType::Int)) => format!("(inp.{} as f64)", field),
+                    Some(Name::Input(_)) => format!("inp.{}", field),
+                    Some(Name::Let { .. }) => format!("l_{}", field),
+                    Some(Name::InputArray(_)) | None => {
+                        return self.error(e.span, format!("cannot generate code for `{}`", name))
+                    }
+                }
+            }
+            ExprKind::Index { array, index } => {
+                let index = self.expr(index)?;
+                let field = snake_case(&array.name);
+                let access = format!("at(&inp.{}, {}, {:?})?", field, index, array.name);
+                match self.names.get(&array.name) {
+                    Some(Name::InputArray(AlnType::Int)) => format!("({} as f64)", access),
+                    Some(Name::InputArray(_)) => access,
+                    _ => {
+                        return self.error(
+                            array.span,
+                            format!("only INPUT arrays can be indexed, not `{}`", array.name),
+                        )
+                    }
+                }
+            }
+            ExprKind::Unary { op, operand } => {
+                let operand = self.expr(operand)?;
+                match op {
+                    UnaryOp::Neg => format!("(-{})", operand),
+                    UnaryOp::Not => format!("(!{})", operand),
+                }
+            }
+            ExprKind::Binary { op, lhs, rhs } => {
+                let l = self.expr(lhs)?;
+                let r = self.expr(rhs)?;
+                match op {
+                    BinaryOp::Div => format!("div({}, {})?", l, r),
+                    BinaryOp::And => format!("({} && {})", l, r),
+                    BinaryOp::Or => format!("({} || {})", l, r),
+                    op => format!("({} {} {})", l, op.as_str(), r),
+                }
+            }
+            ExprKind::Sum { var, lo, hi, body } => {
+                let lo = self.expr(lo)?;
+                let hi = self.expr(hi)?;
+                self.sum_vars.push(var.name.clone());
+                let body = self.expr(body);
+                self.sum_vars.pop();
+                format!(
+                    "sum({}, {}, |s_{}: f64| -> Result<f64, ContractError> {{ Ok({}) }})?",
+                    lo,
+                    hi,
+                    snake_case(&var.name),
+                    body?
+                )
+            }
+            ExprKind::Call { func, args } => {
+                let args = args
+                    .iter()
+                    .map(|a| self.expr(a))
+                    .collect::<Result<Vec<_>, _>>()?;
+                match func {
+                    Builtin::Abs => format!("{}.abs()", args[0]),
+                    Builtin::Clip01 => format!("{}.clamp(0.0, 1.0)", args[0]),
+                    Builtin::Min => format!("{}.min({})", args[0], args[1]),
+                    Builtin::Max => format!("{}.max({})", args[0], args[1]),
+                }
+            }
+        })
+    }
+
+    /// `let c_X = ...;` for every CONST, in declaration order.
+    fn const_prelude(&mut self, out: &mut String) -> Result<(), AlnError> {
+        for c in &self.contract.consts {
+            let value = self.expr(&c.value)?;
+            writeln!(
+                out,
+                "        let c_{} = {};",
+                snake_case(&c.name.name),
+                value
+            )
+            .unwrap();
+        }
+        Ok(())
+    }
+
+    fn module(&mut self, origin: &str) -> Result<String, AlnError> {
+        let c = self.contract;
+        for x in &c.externs {
+            self.names.insert(x.name.name.clone(), Name::External);
+        }
+        for k in &c.consts {
+            self.names.insert(k.name.name.clone(), Name::Const);
+        }
+        for d in &c.inputs {
+            let name = match d.size {
+                Some(_) => Name::InputArray(d.ty),
+                None => Name::Input(d.ty),
+            };
+            self.names.insert(d.name.name.clone(), name);
+        }
+
+        let mut fields = BTreeSet::new();
+        for name in self.names.keys().chain(c.lets.iter().map(|l| &l.name.name)) {
+            if !fields.insert(snake_case(name)) {
+                return self.error(
+                    c.name.span,
+                    format!(
+                        "two names in `{}` map to the Rust name `{}`",
+                        c.name.name,
+                        snake_case(name)
+                    ),
+                );
+            }
+        }
+
+        let mut out = String::new();
+        let module = snake_case(&c.name.name);
+        writeln!(
+            out,
+            "/// Generated from CONTRACT {} in {}.",
+            c.name.name, origin
+        )
+        .unwrap();
+        writeln!(
+            out,
+            "#[allow(unused_variables, unused_parens, clippy::all)]"
+        )
+        .unwrap();
+        writeln!(out, "pub mod {} {{", module).unwrap();
+        writeln!(out, "    #[allow(unused_imports)]").unwrap();
+        writeln!(
+            out,
+            "    use super::{{at, check_len, div, int, sum, ContractError}};\n"
+        )
+        .unwrap();
+        writeln!(out, "    pub const CONTRACT: &str = {:?};", c.name.name).unwrap();
+        writeln!(out, "    pub const SOURCE: &str = {:?};\n", origin).unwrap();
+
+        writeln!(out, "    /// EXTERN constants of the contract.").unwrap();
+        // No `Default` on Constants and Inputs: callers set every field, so a
+        // field added to the spec breaks them at compile time.
+        writeln!(out, "    #[derive(Clone, Debug, PartialEq)]").unwrap();
+        writeln!(out, "    pub struct Constants {{").unwrap();
+        for x in &c.externs {
+            writeln!(out, "        pub {}: f64,", snake_case(&x.name.name)).unwrap();
+        }
+        writeln!(out, "    }}\n").unwrap();
+
+        writeln!(out, "    #[derive(Clone, Debug, PartialEq)]").unwrap();
+        writeln!(out, "    pub struct Inputs {{").unwrap();
+        for d in &c.inputs {
+            let ty = match d.size {
+                Some(_) => format!("Vec<{}>", rust_type(d.ty)),
+                None => rust_type(d.ty).to_string(),
+            };
+            writeln!(out, "        pub {}: {},", snake_case(&d.name.name), ty).unwrap();
+        }
+        writeln!(out, "    }}\n").unwrap();
+
+        // Every LET is reported; OUTPUTs keep their declared type, other LETs
+        // are f64 or bool.
+        let mut let_types = Vec::new();
+        for l in &c.lets {
+            let is_bool = self.is_bool(&l.value);
+            let ty = match c.output(&l.name.name) {
+                Some(d) => d.ty,
+                None if is_bool => AlnType::Bool,
+                None => AlnType::Real,
+            };
+            let_types.push(ty);
+            self.names
+                .insert(l.name.name.clone(), Name::Let { is_bool });
+        }
+        writeln!(out, "    /// OUTPUTs and intermediate LET values.").unwrap();
+        writeln!(out, "    #[derive(Clone, Debug, Default, PartialEq)]").unwrap();
+        writeln!(out, "    pub struct Outputs {{").unwrap();
+        for (l, ty) in c.lets.iter().zip(&let_types) {
+            writeln!(
+                out,
+                "        pub {}: {},",
+                snake_case(&l.name.name),
+                rust_type(*ty)
+            )
+            .unwrap();
+        }
+        writeln!(out, "    }}\n").unwrap();
+
+        writeln!(out, "    #[derive(Clone, Debug, PartialEq)]").unwrap();
+        writeln!(out, "    pub struct Report {{").unwrap();
+        writeln!(out, "        pub outputs: Outputs,").unwrap();
+        for inv in &c.invariants {
+            writeln!(out, "        pub {}: bool,", snake_case(&inv.name.name)).unwrap();
+        }
+        writeln!(out, "    }}\n").unwrap();
+        writeln!(out, "    impl Report {{").unwrap();
+        writeln!(out, "        pub fn all_hold(&self) -> bool {{").unwrap();
+        let conj: Vec<String> = c
+            .invariants
+            .iter()
+            .map(|inv| format!("self.{}", snake_case(&inv.name.name)))
+            .collect();
+        if conj.is_empty() {
+            writeln!(out, "            true").unwrap();
+        } else {
+            writeln!(out, "            {}", conj.join(" && ")).unwrap();
+        }
+        writeln!(out, "        }}\n    }}\n").unwrap();
+
+        // compute(): array sizes, then LETs in source order.
+        writeln!(
+            out,
+            "    /// Check INPUT array sizes and evaluate every LET."
+        )
+        .unwrap();
+        writeln!(
+            out,
+            "    pub fn compute(k: &Constants, inp: &Inputs) -> Result<Outputs, ContractError> {{"
+        )
+        .unwrap();
+        self.const_prelude(&mut out)?;
+        for d in &c.inputs {
+            if let Some(size) = &d.size {
+                let size = self.expr(size)?;
+                writeln!(
+                    out,
+                    "        check_len({:?}, inp.{}.len(), {})?;",
+                    d.name.name,
+                    snake_case(&d.name.name),
+                    size
+                )
+                .unwrap();
+            }
+        }
+        for l in &c.lets {
+            let value = self.expr(&l.value)?;
+            writeln!(
+                out,
+                "        let l_{} = {};",
+                snake_case(&l.name.name),
+                value
+            )
+            .unwrap();
+        }
+        writeln!(out, "        Ok(Outputs {{").unwrap();
+        for (l, ty) in c.lets.iter().zip(&let_types) {
+            let name = snake_case(&l.name.name);
+            match ty {
+                AlnType::Int => writeln!(out, "            {}: int(l_{})?,", name, name),
+                _ => writeln!(out, "            {}: l_{},", name, name),
+            }
+            .unwrap();
+        }
+        writeln!(out, "        }})\n    }}\n").unwrap();
+
+        // One check function per INVARIANT.
+        for inv in &c.invariants {
+            writeln!(out, "    /// INVARIANT {}.", inv.name.name).unwrap();
+            writeln!(
+                out,
+                "    pub fn check_{}(k: &Constants, inp: &Inputs, out: &Outputs) -> Result<bool, ContractError> {{",
+                snake_case(&inv.name.name)
+            )
+            .unwrap();
+            self.const_prelude(&mut out)?;
+            for (l, ty) in c.lets.iter().zip(&let_types) {
+                let name = snake_case(&l.name.name);
+                match ty {
+                    AlnType::Int => writeln!(out, "        let l_{} = out.{} as f64;", name, name),
+                    _ => writeln!(out, "        let l_{} = out.{};", name, name),
+                }
+                .unwrap();
+            }
+            let expr = self.expr(&inv.expr)?;
+            writeln!(out, "        Ok({})\n    }}\n", expr).unwrap();
+        }
+
+        writeln!(
+            out,
+            "    /// Compute all outputs and evaluate every INVARIANT."
+        )
+        .unwrap();
+        writeln!(
+            out,
+            "    pub fn check(k: &Constants, inp: &Inputs) -> Result<Report, ContractError> {{"
+        )
+        .unwrap();
+        writeln!(out, "        let outputs = compute(k, inp)?;").unwrap();
+        writeln!(out, "        Ok(Report {{").unwrap();
+        for inv in &c.invariants {
+            let name = snake_case(&inv.name.name);
+            writeln!(
+                out,
+                "            {}: check_{}(k, inp, &outputs)?,",
+                name, name
+            )
+            .unwrap();
+        }
+        writeln!(out, "            outputs,").unwrap();
+        writeln!(out, "        }})\n    }}\n}}\n").unwrap();
+        Ok(out)
+    }
+}
+
+/// Rust source for every contract of `file`, one module per contract, meant
+/// to be `include!`d by `crate::contracts`. `origin` is the path recorded in
+/// the generated docs; `src` is the parsed text, used for error locations.
+pub fn generate_rust(file: &AlnFile, src: &str, origin: &str) -> Result<String, AlnError> {
+    let mut out = String::new();
+    for contract in &file.contracts {
+        let mut gen = Gen {
+            src,
+            contract,
+            names: BTreeMap::new(),
+            sum_vars: Vec::new(),
+        };
+        out.push_str(&gen.module(origin)?);
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aln::parse_aln;
+
+    fn generate(src: &str) -> Result<String, AlnError> {
+        generate_rust(&parse_aln(src).unwrap(), src, "spec/t.aln")
+    }
+
+    #[test]
+    fn test_snake_case() {
+        assert_eq!(
+            snake_case("NonIncreasingResidual"),
+            "non_increasing_residual"
+        );
+        assert_eq!(snake_case("V_prev"), "v_prev");
+        assert_eq!(snake_case("NUM_CHANNELS"), "num_channels");
+        assert_eq!(snake_case("GWP_cybo"), "gwp_cybo");
+        assert_eq!(snake_case("r2D"), "r2_d");
+        assert_eq!(snake_case("Type"), "type_");
+    }
+
+    #[test]
+    fn test_module_layout() {
+        let src = "CONTRACT Gate\n  EXTERN N;\n  EXTERN Eps;\n  CONST Half = 0.5;\n  \
+                   INPUT x[N] : REAL;\n  INPUT cnt : INT;\n  OUTPUT Mean : REAL;\n  \
+                   LET Mean = SUM(i IN 0..N-1) (x[i]) / N;\n  LET Count = cnt;\n  \
+                   INVARIANT Low: Mean <= Half + Eps;\nEND;\n";
+        let code = generate(src).unwrap();
+        for expected in [
+            "/// Generated from CONTRACT Gate in spec/t.aln.",
+            "pub mod gate {",
+            "pub const CONTRACT: &str = \"Gate\";",
+            // EXTERNs in declaration order; no Default on Constants/Inputs.
+            "#[derive(Clone, Debug, PartialEq)]\n    pub struct Constants {\n        pub n: f64,\n        pub eps: f64,\n    }",
+            "#[derive(Clone, Debug, PartialEq)]\n    pub struct Inputs {\n        pub x: Vec<f64>,\n        pub cnt: i64,\n    }",
+            "#[derive(Clone, Debug, Default, PartialEq)]\n    pub struct Outputs {\n        pub mean: f64,\n        pub count: f64,\n    }",
+            "let c_half = 0.5_f64;",
+            "check_len(\"x\", inp.x.len(), k.n)?;",
+            "at(&inp.x, s_i, \"x\")?",
+            "div(sum(0.0_f64, (k.n - 1.0_f64)",
+            "let l_count = (inp.cnt as f64);",
+            "pub fn check_low(k: &Constants, inp: &Inputs, out: &Outputs)",
+            "Ok((l_mean <= (c_half + k.eps)))",
+            "            self.low\n",
+        ] {
+            assert!(code.contains(expected), "missing {:?} in\n{}", expected, code);
+        }
+    }
+
+    #[test]
+    fn test_int_outputs_and_empty_contracts() {
+        let src = "CONTRACT A\n  INPUT k : INT;\n  OUTPUT Twice : INT;\n  \
+                   LET Twice = 2 * k;\n  INVARIANT Pos: Twice >= 0;\nEND;\n\
+                   CONTRACT B\nEND;\n";
+        let code = generate(src).unwrap();
+        assert!(code.contains("pub twice: i64,"), "{}", code);
+        assert!(code.contains("twice: int(l_twice)?,"), "{}", code);
+        assert!(code.contains("let l_twice = out.twice as f64;"), "{}", code);
+        // No invariants: all_hold() is vacuously true.
+        assert!(code.contains("pub mod b {"), "{}", code);
+        assert!(code.contains("            true\n"), "{}", code);
+    }
+
+    #[test]
+    fn test_rust_name_collisions_are_rejected() {
+        let src = "CONTRACT C\n  INPUT Vnext : REAL;\n  LET VNEXT = Vnext;\nEND;\n";
+        let err = generate(src).unwrap_err();
+        assert!(
+            err.message.contains("map to the Rust name `vnext`"),
+            "{}",
+            err
+        );
+        assert_eq!((err.line, err.column), (1, 10));
+    }
+}
@@ -0,0 +1,323 @@
+use super::ast::Span;
+use super::AlnError;
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum Tok {
+    Ident(String),
+    Number(f64),
+    // keywords
+    Contract,
+    Input,
+    Output,
+    Const,
+    Extern,
+    Let,
+    Invariant,
+    End,
+    Sum,
+    In,
+    And,
+    Or,
+    Not,
+    True,
+    False,
+    Real,
+    Int,
+    Bool,
+    // punctuation
+    LBracket,
+    RBracket,
+    LParen,
+    RParen,
+    Colon,
+    Semi,
+    Comma,
+    DotDot,
+    Plus,
+    Minus,
+    Star,
+    Slash,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    EqEq,
+    Ne,
+    Assign,
+    Eof,
+}
+
+impl Tok {
+    pub(crate) fn describe(&self) -> String {
+        match self {
+            Tok::Ident(name) => format!("identifier `{}`", name),
+            Tok::Number(x) => format!("number `{}`", x),
+            Tok::Eof => "end of file".to_string(),
+            other => format!("`{}`", other.text()),
+        }
+    }
+
+    fn text(&self) -> &'static str {
+        match self {
+            Tok::Contract => "CONTRACT",
+            Tok::Input => "INPUT",
+            Tok::Output => "OUTPUT",
+            Tok::Const => "CONST",
+            Tok::Extern => "EXTERN",
+            Tok::Let => "LET",
+            Tok::Invariant => "INVARIANT",
+            Tok::End => "END",
+            Tok::Sum => "SUM",
+            Tok::In => "IN",
+            Tok::And => "AND",
+            Tok::Or => "OR",
+            Tok::Not => "NOT",
+            Tok::True => "TRUE",
+            Tok::False => "FALSE",
+            Tok::Real => "REAL",
+            Tok::Int => "INT",
+            Tok::Bool => "BOOL",
+            Tok::LBracket => "[",
+            Tok::RBracket => "]",
+            Tok::LParen => "(",
+            Tok::RParen => ")",
+            Tok::Colon => ":",
+            Tok::Semi => ";",
+            Tok::Comma => ",",
+            Tok::DotDot => "..",
+            Tok::Plus => "+",
+            Tok::Minus => "-",
+            Tok::Star => "*",
+            Tok::Slash => "/",
+            Tok::Lt => "<",
+            Tok::Le => "<=",
+            Tok::Gt => ">",
+            Tok::Ge => ">=",
+            Tok::EqEq => "==",
+            Tok::Ne => "!=",
+            Tok::Assign => "=",
+            Tok::Ident(_) | Tok::Number(_) | Tok::Eof => "",
+        }
+    }
+}
+
+fn keyword(word: &str) -> Option<Tok> {
+    Some(match word {
+        "CONTRACT" => Tok::Contract,
+        "INPUT" => Tok::Input,
+        "OUTPUT" => Tok::Output,
+        "CONST" => Tok::Const,
+        "EXTERN" => Tok::Extern,
+        "LET" => Tok::Let,
+        "INVARIANT" => Tok::Invariant,
+        "END" => Tok::End,
+        "SUM" => Tok::Sum,
+        "IN" => Tok::In,
+        "AND" => Tok::And,
+        "OR" => Tok::Or,
+        "NOT" => Tok::Not,
+        "TRUE" => Tok::True,
+        "FALSE" => Tok::False,
+        "REAL" => Tok::Real,
+        "INT" => Tok::Int,
+        "BOOL" => Tok::Bool,
+        _ => return None,
+    })
+}
+
+/// Split `.aln` source into tokens. Keywords are upper-case; `//` starts a
+/// line comment. A number followed by `..` is an integer range bound, so
+/// `0..N` lexes as `0`, `..`, `N`.
+pub(crate) fn tokenize(src: &str) -> Result<Vec<(Tok, Span)>, AlnError> {
+    let bytes = src.as_bytes();
+    let mut toks = Vec::new();
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let c = bytes[i];
+        let start = i;
+        if c.is_ascii_whitespace() {
+            i += 1;
+            continue;
+        }
+        if c == b'/' && bytes.get(i + 1) == Some(&b'/') {
+            while i < bytes.len() && bytes[i] != b'\n' {
+                i += 1;
+            }
+            continue;
+        }
+        if c.is_ascii_alphabetic() || c == b'_' {
+            while i < bytes.len() && (bytes[i].is_ascii_alphanumeric() || bytes[i] == b'_') {
+                i += 1;
+            }
+            let word = &src[start..i];
+            let tok = keyword(word).unwrap_or_else(|| Tok::Ident(word.to_string()));
+            toks.push((tok, Span::new(start, i)));
+            continue;
+        }
+        if c.is_ascii_digit() {
+            while i < bytes.len() && bytes[i].is_ascii_digit() {
+                i += 1;
+            }
+            if bytes.get(i) == Some(&b'.') && bytes.get(i + 1).is_some_and(u8::is_ascii_digit) {
+                i += 1;
+                while i < bytes.len() && bytes[i].is_ascii_digit() {
+                    i += 1;
+                }
+            }
+            if matches!(bytes.get(i), Some(b'e') | Some(b'E')) {
+                let mut j = i + 1;
+                if matches!(bytes.get(j), Some(b'+') | Some(b'-')) {
+                    j += 1;
+                }
+                if bytes.get(j).is_some_and(u8::is_ascii_digit) {
+                    i = j;
+                    while i < bytes.len() && bytes[i].is_ascii_digit() {
+                        i += 1;
+                    }
+                }
+            }
+            let text = &src[start..i];
+            let x = text.parse::<f64>().map_err(|_| {
+                AlnError::new(
+                    src,
+                    Span::new(start, i),
+                    format!("invalid number `{}`", text),
+                )
+            })?;
+            toks.push((Tok::Number(x), Span::new(start, i)));
+            continue;
+        }
+
+        let next = bytes.get(i + 1).copied();
+        let (tok, len) = match (c, next) {
+            (b'.', Some(b'.')) => (Tok::DotDot, 2),
+            (b'<', Some(b'=')) => (Tok::Le, 2),
+            (b'<', Some(b'>')) => (Tok::Ne, 2),
+            (b'>', Some(b'=')) => (Tok::Ge, 2),
+            (b'=', Some(b'=')) => (Tok::EqEq, 2),
+            (b'!', Some(b'=')) => (Tok::Ne, 2),
+            (b'<', _) => (Tok::Lt, 1),
+            (b'>', _) => (Tok::Gt, 1),
+            (b'=', _) => (Tok::Assign, 1),
+            (b'[', _) => (Tok::LBracket, 1),
+            (b']', _) => (Tok::RBracket, 1),
+            (b'(', _) => (Tok::LParen, 1),
+            (b')', _) => (Tok::RParen, 1),
+            (b':', _) => (Tok::Colon, 1),
+            (b';', _) => (Tok::Semi, 1),
+            (b',', _) => (Tok::Comma, 1),
+            (b'+', _) => (Tok::Plus, 1),
+            (b'-', _) => (Tok::Minus, 1),
+            (b'*', _) => (Tok::Star, 1),
+            (b'/', _) => (Tok::Slash, 1),
+            _ => {
+                let ch = src[start..].chars().next().unwrap_or('?');
+                return Err(AlnError::new(
+                    src,
+                    Span::new(start, start + ch.len_utf8()),
+                    format!("unexpected character `{}`", ch),
+                ));
+            }
+        };
+        i += len;
+        toks.push((tok, Span::new(start, i)));
+    }
+
+    toks.push((Tok::Eof, Span::new(src.len(), src.len())));
+    Ok(toks)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn toks(src: &str) -> Vec<Tok> {
+        tokenize(src).unwrap().into_iter().map(|(t, _)| t).collect()
+    }
+
+    #[test]
+    fn test_keywords_identifiers_and_comments() {
+        assert_eq!(
+            toks("EXTERN EPS; // caller-bound\nLET v_1 = Sum;"),
+            vec![
+                Tok::Extern,
+                Tok::Ident("EPS".to_string()),
+                Tok::Semi,
+                Tok::Let,
+                Tok::Ident("v_1".to_string()),
+                Tok::Assign,
+                // Keywords are case-sensitive.
+                Tok::Ident("Sum".to_string()),
+                Tok::Semi,
+                Tok::Eof,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_numbers_and_ranges() {
+        assert_eq!(
+            toks("0..N-1"),
+            vec![
+                Tok::Number(0.0),
+                Tok::DotDot,
+                Tok::Ident("N".to_string()),
+                Tok::Minus,
+                Tok::Number(1.0),
+                Tok::Eof,
+            ]
+        );
+        assert_eq!(
+            toks("2.5 1e-3 4E2 7e"),
+            vec![
+                Tok::Number(2.5),
+                Tok::Number(1e-3),
+                Tok::Number(400.0),
+                Tok::Number(7.0),
+                Tok::Ident("e".to_string()),
+                Tok::Eof,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_operators_and_spans() {
+        assert_eq!(
+            toks("<= <> != == < >= ="),
+            vec![
+                Tok::Le,
+                Tok::Ne,
+                Tok::Ne,
+                Tok::EqEq,
+                Tok::Lt,
+                Tok::Ge,
+                Tok::Assign,
+                Tok::Eof,
+            ]
+        );
+        let spans: Vec<Span> = tokenize("a <= 10")
+            .unwrap()
+            .into_iter()
+            .map(|(_, s)| s)
+            .collect();
+        assert_eq!(
+            spans,
+            vec![
+                Span::new(0, 1),
+                Span::new(2, 4),
+                Span::new(5, 7),
+                Span::new(7, 7)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unexpected_character() {
+        let err = tokenize("LET x = 1;\nLET y = x # 2;").unwrap_err();
+        assert_eq!((err.line, err.column), (2, 11));
+        assert_eq!(err.message, "unexpected character `#`");
+        let err = tokenize("x = µ").unwrap_err();
+        assert_eq!(err.span, Span::new(4, 6));
+    }
+}
@@ -0,0 +1,125 @@
+//! `.aln` contract language: the CONTRACT blocks under `spec/`.
+//!
+//! ```text
+//! CONTRACT Name
+//!   EXTERN NUM_CHANNELS;
+//!   EXTERN EPS;
+//!   CONST N = 9;
+//!   INPUT  r_x[NUM_CHANNELS] : REAL;
+//!   OUTPUT V_next            : REAL;
+//!   LET V_next = SUM(i IN 0..NUM_CHANNELS-1) ( w[i] * r_x[i] );
+//!   INVARIANT NonIncreasingResidual: V_next <= V_prev + EPS;
+//! END;
+//! ```
+//!
+//! EXTERN names (NUM_CHANNELS, EPS) are numeric constants bound by the
+//! caller. Every name must be declared; an undeclared one is an error, so a
+//! typo in a spec cannot turn into a new constant.
+//!
+//! This module has no `crate::` imports so that `build.rs` can compile it
+//! through `#[path]`; evaluation against shard data lives in `aln_interp`.
+
+pub mod ast;
+pub mod codegen;
+mod lexer;
+pub mod parser;
+
+pub use ast::{AlnFile, Contract, Span};
+pub use parser::parse_aln;
+
+use std::error::Error;
+use std::fmt;
+
+/// Parse or check error with its location. `line` and `column` are 1-based.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AlnError {
+    pub message: String,
+    pub span: Span,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl AlnError {
+    pub(crate) fn new(src: &str, span: Span, message: String) -> Self {
+        let before = &src[..span.start.min(src.len())];
+        let line = before.matches('\n').count() + 1;
+        let column = before
+            .rfind('\n')
+            .map_or(before.len(), |nl| before.len() - nl - 1)
+            + 1;
+        AlnError {
+            message,
+            span,
+            line,
+            column,
+        }
+    }
+
+    /// Compiler-style report with the offending line and a caret underline.
+    pub fn render(&self, src: &str, path: &str) -> String {
+        let text = src.lines().nth(self.line - 1).unwrap_or("");
+        let width = (self.span.end.saturating_sub(self.span.start))
+            .clamp(1, text.len().saturating_sub(self.column - 1).max(1));
+        let gutter = self.line.to_string().len();
+        format!(
+            "error: {msg}\n{pad} --> {path}:{line}:{col}\n{pad} |\n{line:>gutter$} | {text}\n{pad} | {marker}{carets}\n",
+            msg = self.message,
+            pad = " ".repeat(gutter),
+            path = path,
+            line = self.line,
+            col = self.column,
+            gutter = gutter,
+            text = text,
+            marker = " ".repeat(self.column - 1),
+            carets = "^".repeat(width),
+        )
+    }
+}
+
+impl fmt::Display for AlnError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}: {}", self.line, self.column, self.message)
+    }
+}
+
+impl Error for AlnError {}
+
+#[cfg(test)]
+mod tests {
+    use super::ast::{BinaryOp, ExprKind};
+    use super::*;
+
+    const CORRIDORS: &str = include_str!("../../../spec/corridors.cybo.aln");
+    const LCA_GATES: &str = include_str!("../../../spec/lca-gates.cybo.aln");
+
+    #[test]
+    fn test_parse_spec_files() {
+        let file = parse_aln(CORRIDORS).unwrap();
+        let c = file.contract("CybocinderLyapunov").unwrap();
+        assert_eq!(c.inputs.len(), 3);
+        assert!(c.input("r_x").unwrap().size.is_some());
+        let externs: Vec<&str> = c.externs.iter().map(|x| x.name.name.as_str()).collect();
+        assert_eq!(externs, vec!["NUM_CHANNELS", "EPS"]);
+        assert!(matches!(c.lets[0].value.kind, ExprKind::Sum { .. }));
+        match &c.invariants[0].expr.kind {
+            ExprKind::Binary { op, .. } => assert_eq!(*op, BinaryOp::Le),
+            other => panic!("unexpected invariant {:?}", other),
+        }
+
+        let file = parse_aln(LCA_GATES).unwrap();
+        assert_eq!(file.contracts[0].invariants[0].name.name, "PrimaryLca");
+    }
+
+    #[test]
+    fn test_errors_point_at_source() {
+        let src = "CONTRACT C\n  INPUT x : REAL;\n  INVARIANT Ok: x[0] < 1;\nEND;\n";
+        let err = parse_aln(src).unwrap_err();
+        assert_eq!((err.line, err.column), (3, 17));
+        assert!(err.message.contains("not an array"), "{}", err);
+        assert!(err.render(src, "c.aln").contains("c.aln:3:17"));
+
+        let err = parse_aln("CONTRACT C\n  INPUT x REAL;\nEND;").unwrap_err();
+        assert_eq!(err.line, 2);
+        assert!(err.message.contains("`:`"), "{}", err);
+    }
+}
@@ -0,0 +1,817 @@
+use super::ast::*;
+use super::lexer::{tokenize, Tok};
+use super::AlnError;
+use std::collections::HashMap;
+
+struct Parser<'a> {
+    src: &'a str,
+    toks: Vec<(Tok, Span)>,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> &Tok {
+        &self.toks[self.pos].0
+    }
+
+    fn span(&self) -> Span {
+        self.toks[self.pos].1
+    }
+
+    fn bump(&mut self) -> (Tok, Span) {
+        let t = self.toks[self.pos].clone();
+        if t.0 != Tok::Eof {
+            self.pos += 1;
+        }
+        t
+    }
+
+    fn eat(&mut self, tok: &Tok) -> bool {
+        if self.peek() == tok {
+            self.bump();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn error<T>(&self, span: Span, message: String) -> Result<T, AlnError> {
+        Err(AlnError::new(self.src, span, message))
+    }
+
+    fn unexpected<T>(&self, expected: &str) -> Result<T, AlnError> {
+        self.error(
+            self.span(),
+            format!("expected {}, found {}", expected, self.peek().describe()),
+        )
+    }
+
+    fn expect(&mut self, tok: Tok, expected: &str) -> Result<Span, AlnError> {
+        if *self.peek() == tok {
+            Ok(self.bump().1)
+        } else {
+            self.unexpected(expected)
+        }
+    }
+
+    fn ident(&mut self, what: &str) -> Result<Ident, AlnError> {
+        match self.peek().clone() {
+            Tok::Ident(name) => {
+                let span = self.bump().1;
+                Ok(Ident { name, span })
+            }
+            _ => self.unexpected(what),
+        }
+    }
+
+    fn file(&mut self) -> Result<AlnFile, AlnError> {
+        let mut contracts: Vec<Contract> = Vec::new();
+        while *self.peek() != Tok::Eof {
+            let contract = self.contract()?;
+            if contracts.iter().any(|c| c.name.name == contract.name.name) {
+                return self.error(
+                    contract.name.span,
+                    format!("contract `{}` is defined twice", contract.name.name),
+                );
+            }
+            contracts.push(contract);
+        }
+        Ok(AlnFile { contracts })
+    }
+
+    fn contract(&mut self) -> Result<Contract, AlnError> {
+        let start = self.expect(Tok::Contract, "`CONTRACT`")?;
+        let name = self.ident("contract name")?;
+        let mut contract = Contract {
+            name,
+            consts: Vec::new(),
+            externs: Vec::new(),
+            inputs: Vec::new(),
+            outputs: Vec::new(),
+            lets: Vec::new(),
+            invariants: Vec::new(),
+            span: start,
+        };
+
+        loop {
+            let item_start = self.span();
+            match self.peek() {
+                Tok::Input | Tok::Output => {
+                    let is_input = self.bump().0 == Tok::Input;
+                    let name = self.ident("declaration name")?;
+                    let size = if self.eat(&Tok::LBracket) {
+                        let size = self.expr()?;
+                        self.expect(Tok::RBracket, "`]`")?;
+                        Some(size)
+                    } else {
+                        None
+                    };
+                    self.expect(Tok::Colon, "`:` before the type")?;
+                    let ty = match self.bump() {
+                        (Tok::Real, _) => AlnType::Real,
+                        (Tok::Int, _) => AlnType::Int,
+                        (Tok::Bool, _) => AlnType::Bool,
+                        (tok, span) => {
+                            return self.error(
+                                span,
+                                format!("expected REAL, INT or BOOL, found {}", tok.describe()),
+                            )
+                        }
+                    };
+                    let end = self.expect(Tok::Semi, "`;`")?;
+                    let decl = Decl {
+                        name,
+                        size,
+                        ty,
+                        span: item_start.to(end),
+                    };
+                    if is_input {
+                        contract.inputs.push(decl);
+                    } else {
+                        contract.outputs.push(decl);
+                    }
+                }
+                Tok::Const => {
+                    self.bump();
+                    let name = self.ident("constant name")?;
+                    self.expect(Tok::Assign, "`=`")?;
+                    let value = self.expr()?;
+                    let end = self.expect(Tok::Semi, "`;`")?;
+                    contract.consts.push(ConstDef {
+                        name,
+                        value,
+                        span: item_start.to(end),
+                    });
+                }
+                Tok::Extern => {
+                    self.bump();
+                    let name = self.ident("constant name")?;
+                    let end = self.expect(Tok::Semi, "`;`")?;
+                    contract.externs.push(ExternDef {
+                        name,
+                        span: item_start.to(end),
+                    });
+                }
+                Tok::Let => {
+                    self.bump();
+                    let name = self.ident("LET target")?;
+                    self.expect(Tok::Assign, "`=`")?;
+                    let value = self.expr()?;
+                    let end = self.expect(Tok::Semi, "`;`")?;
+                    contract.lets.push(Let {
+                        name,
+                        value,
+                        span: item_start.to(end),
+                    });
+                }
+                Tok::Invariant => {
+                    self.bump();
+                    let name = self.ident("invariant name")?;
+                    self.expect(Tok::Colon, "`:` after the invariant name")?;
+                    let expr = self.expr()?;
+                    let end = self.expect(Tok::Semi, "`;`")?;
+                    contract.invariants.push(Invariant {
+                        name,
+                        expr,
+                        span: item_start.to(end),
+                    });
+                }
+                Tok::End => {
+                    self.bump();
+                    let end = self.expect(Tok::Semi, "`;` after END")?;
+                    contract.span = contract.span.to(end);
+                    break;
+                }
+                Tok::Eof => {
+                    return self.error(
+                        contract.name.span,
+                        format!("contract `{}` is missing `END;`", contract.name.name),
+                    )
+                }
+                _ => return self.unexpected("INPUT, OUTPUT, CONST, EXTERN, LET, INVARIANT or END"),
+            }
+        }
+
+        Checker::new(self.src).check(&mut contract)?;
+        Ok(contract)
+    }
+
+    fn expr(&mut self) -> Result<Expr, AlnError> {
+        self.or_expr()
+    }
+
+    fn binary(op: BinaryOp, lhs: Expr, rhs: Expr) -> Expr {
+        Expr {
+            span: lhs.span.to(rhs.span),
+            kind: ExprKind::Binary {
+                op,
+                lhs: Box::new(lhs),
+                rhs: Box::new(rhs),
+            },
+        }
+    }
+
+    fn or_expr(&mut self) -> Result<Expr, AlnError> {
+        let mut lhs = self.and_expr()?;
+        while self.eat(&Tok::Or) {
+            let rhs = self.and_expr()?;
+            lhs = Self::binary(BinaryOp::Or, lhs, rhs);
+        }
+        Ok(lhs)
+    }
+
+    fn and_expr(&mut self) -> Result<Expr, AlnError> {
+        let mut lhs = self.not_expr()?;
+        while self.eat(&Tok::And) {
+            let rhs = self.not_expr()?;
+            lhs = Self::binary(BinaryOp::And, lhs, rhs);
+        }
+        Ok(lhs)
+    }
+
+    fn not_expr(&mut self) -> Result<Expr, AlnError> {
+        if *self.peek() == Tok::Not {
+            let start = self.bump().1;
+            let operand = self.not_expr()?;
+            return Ok(Expr {
+                span: start.to(operand.span),
+                kind: ExprKind::Unary {
+                    op: UnaryOp::Not,
+                    operand: Box::new(operand),
+                },
+            });
+        }
+        self.comparison()
+    }
+
+    /// Comparisons do not chain: `a < b < c` is rejected.
+    fn comparison(&mut self) -> Result<Expr, AlnError> {
+        let lhs = self.additive()?;
+        let op = match self.peek() {
+            Tok::Lt => BinaryOp::Lt,
+            Tok::Le => BinaryOp::Le,
+            Tok::Gt => BinaryOp::Gt,
+            Tok::Ge => BinaryOp::Ge,
+            Tok::EqEq => BinaryOp::Eq,
+            Tok::Ne => BinaryOp::Ne,
+            Tok::Assign => {
+                return self.error(self.span(), "use `==` to compare for equality".to_string())
+            }
+            _ => return Ok(lhs),
+        };
+        self.bump();
+        let rhs = self.additive()?;
+        if matches!(
+            self.peek(),
+            Tok::Lt | Tok::Le | Tok::Gt | Tok::Ge | Tok::EqEq | Tok::Ne
+        ) {
+            return self.error(
+                self.span(),
+                "comparisons cannot be chained; combine them with AND".to_string(),
+            );
+        }
+        Ok(Self::binary(op, lhs, rhs))
+    }
+
+    fn additive(&mut self) -> Result<Expr, AlnError> {
+        let mut lhs = self.multiplicative()?;
+        loop {
+            let op = match self.peek() {
+                Tok::Plus => BinaryOp::Add,
+                Tok::Minus => BinaryOp::Sub,
+                _ => return Ok(lhs),
+            };
+            self.bump();
+            let rhs = self.multiplicative()?;
+            lhs = Self::binary(op, lhs, rhs);
+        }
+    }
+
+    fn multiplicative(&mut self) -> Result<Expr, AlnError> {
+        let mut lhs = self.unary()?;
+        loop {
+            let op = match self.peek() {
+                Tok::Star => BinaryOp::Mul,
+                Tok::Slash => BinaryOp::Div,
+                _ => return Ok(lhs),
+            };
+            self.bump();
+            let rhs = self.unary()?;
+            lhs = Self::binary(op, lhs, rhs);
+        }
+    }
+
+    fn unary(&mut self) -> Result<Expr, AlnError> {
+        if *self.peek() == Tok::Minus {
+            let start = self.bump().1;
+            let operand = self.unary()?;
+            return Ok(Expr {
+                span: start.to(operand.span),
+                kind: ExprKind::Unary {
+                    op: UnaryOp::Neg,
+                    operand: Box::new(operand),
+                },
+            });
+        }
+        self.primary()
+    }
+
+    fn primary(&mut self) -> Result<Expr, AlnError> {
+        let (tok, span) = self.bump();
+        let kind = match tok {
+            Tok::Number(x) => ExprKind::Number(x),
+            Tok::True => ExprKind::Bool(true),
+            Tok::False => ExprKind::Bool(false),
+            Tok::LParen => {
+                let inner = self.expr()?;
+                let end = self.expect(Tok::RParen, "`)`")?;
+                return Ok(Expr {
+                    kind: inner.kind,
+                    span: span.to(end),
+                });
+            }
+            Tok::Sum => {
+                self.expect(Tok::LParen, "`(` after SUM")?;
+                let var = self.ident("SUM index variable")?;
+                self.expect(Tok::In, "`IN`")?;
+                let lo = self.additive()?;
+                self.expect(Tok::DotDot, "`..` in SUM range")?;
+                let hi = self.additive()?;
+                self.expect(Tok::RParen, "`)` after SUM range")?;
+                self.expect(Tok::LParen, "`(` before SUM body")?;
+                let body = self.expr()?;
+                let end = self.expect(Tok::RParen, "`)` after SUM body")?;
+                return Ok(Expr {
+                    kind: ExprKind::Sum {
+                        var,
+                        lo: Box::new(lo),
+                        hi: Box::new(hi),
+                        body: Box::new(body),
+                    },
+                    span: span.to(end),
+                });
+            }
+            Tok::Ident(name) => {
+                if self.eat(&Tok::LBracket) {
+                    let index = self.expr()?;
+                    let end = self.expect(Tok::RBracket, "`]`")?;
+                    return Ok(Expr {
+                        kind: ExprKind::Index {
+                            array: Ident { name, span },
+                            index: Box::new(index),
+                        },
+                        span: span.to(end),
+                    });
+                }
+                if *self.peek() == Tok::LParen {
+                    let func = match Builtin::from_name(&name) {
+                        Some(f) => f,
+                        None => {
+                            return self.error(
+                                span,
+                                format!(
+                                    "unknown function `{}` (expected ABS, MIN, MAX or CLIP01)",
+                                    name
+                                ),
+                            )
+                        }
+                    };
+                    self.bump();
+                    let mut args = Vec::new();
+                    if *self.peek() != Tok::RParen {
+                        loop {
+                            args.push(self.expr()?);
+                            if !self.eat(&Tok::Comma) {
+                                break;
+                            }
+                        }
+                    }
+                    let end = self.expect(Tok::RParen, "`)` or `,`")?;
+                    if args.len() != func.arity() {
+                        return self.error(
+                            span.to(end),
+                            format!(
+                                "`{}` takes {} argument(s), found {}",
+                                name,
+                                func.arity(),
+                                args.len()
+                            ),
+                        );
+                    }
+                    return Ok(Expr {
+                        kind: ExprKind::Call { func, args },
+                        span: span.to(end),
+                    });
+                }
+                ExprKind::Var(name)
+            }
+            tok => {
+                return self.error(
+                    span,
+                    format!("expected an expression, found {}", tok.describe()),
+                )
+            }
+        };
+        Ok(Expr { kind, span })
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Num,
+    Bool,
+}
+
+impl Kind {
+    fn of(ty: AlnType) -> Kind {
+        if ty.is_numeric() {
+            Kind::Num
+        } else {
+            Kind::Bool
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Kind::Num => "numeric",
+            Kind::Bool => "boolean",
+        }
+    }
+}
+
+/// EXTERN constants resolve as `Const`: both are usable in array sizes.
+#[derive(Clone, Copy)]
+enum Symbol {
+    Const,
+    Input { ty: AlnType, array: bool },
+    Output { ty: AlnType, array: bool },
+    Local(Kind),
+}
+
+/// Name resolution and type checking of one contract.
+struct Checker<'a> {
+    src: &'a str,
+    symbols: HashMap<String, Symbol>,
+    /// LET-bound names visible so far (LETs evaluate in source order).
+    bound: HashMap<String, Kind>,
+    sum_vars: Vec<String>,
+}
+
+impl<'a> Checker<'a> {
+    fn new(src: &'a str) -> Self {
+        Checker {
+            src,
+            symbols: HashMap::new(),
+            bound: HashMap::new(),
+            sum_vars: Vec::new(),
+        }
+    }
+
+    fn error<T>(&self, span: Span, message: String) -> Result<T, AlnError> {
+        Err(AlnError::new(self.src, span, message))
+    }
+
+    fn declare(&mut self, name: &Ident, symbol: Symbol) -> Result<(), AlnError> {
+        if self.symbols.insert(name.name.clone(), symbol).is_some() {
+            return self.error(name.span, format!("`{}` is declared twice", name.name));
+        }
+        Ok(())
+    }
+
+    fn check(mut self, contract: &mut Contract) -> Result<(), AlnError> {
+        for c in &contract.consts {
+            self.declare(&c.name, Symbol::Const)?;
+        }
+        for x in &contract.externs {
+            self.declare(&x.name, Symbol::Const)?;
+        }
+        for (decls, is_input) in [(&contract.inputs, true), (&contract.outputs, false)] {
+            for d in decls {
+                let array = d.size.is_some();
+                let symbol = if is_input {
+                    Symbol::Input { ty: d.ty, array }
+                } else {
+                    Symbol::Output { ty: d.ty, array }
+                };
+                self.declare(&d.name, symbol)?;
+            }
+        }
+
+        for c in &contract.consts {
+            self.expect_kind(&c.value, Kind::Num, true, "CONST value")?;
+        }
+        for d in contract.inputs.iter().chain(&contract.outputs) {
+            if let Some(size) = &d.size {
+                self.expect_kind(size, Kind::Num, true, "array size")?;
+            }
+        }
+
+        for l in &contract.lets {
+            let kind = self.infer(&l.value, false)?;
+            match self.symbols.get(&l.name.name).copied() {
+                Some(Symbol::Output { ty, array }) => {
+                    if array {
+                        return self.error(
+                            l.name.span,
+                            format!("array OUTPUT `{}` cannot be bound by LET", l.name.name),
+                        );
+                    }
+                    if Kind::of(ty) != kind {
+                        return self.error(
+                            l.value.span,
+                            format!(
+                                "`{}` is declared {} but LET gives a {} value",
+                                l.name.name,
+                                ty,
+                                kind.name()
+                            ),
+                        );
+                    }
+                }
+                Some(Symbol::Input { .. }) | Some(Symbol::Const) => {
+                    return self.error(
+                        l.name.span,
+                        format!(
+                            "cannot LET `{}`: it is an INPUT, CONST or EXTERN",
+                            l.name.name
+                        ),
+                    )
+                }
+                Some(Symbol::Local(_)) => {
+                    return self.error(
+                        l.name.span,
+                        format!("`{}` is bound by LET twice", l.name.name),
+                    )
+                }
+                None => {
+                    self.symbols
+                        .insert(l.name.name.clone(), Symbol::Local(kind));
+                }
+            }
+            self.bound.insert(l.name.name.clone(), kind);
+        }
+
+        for d in &contract.outputs {
+            if !self.bound.contains_key(&d.name.name) {
+                return self.error(
+                    d.span,
+                    format!("OUTPUT `{}` is never bound by LET", d.name.name),
+                );
+            }
+        }
+        for inv in &contract.invariants {
+            self.expect_kind(&inv.expr, Kind::Bool, false, "INVARIANT")?;
+        }
+        Ok(())
+    }
+
+    fn expect_kind(
+        &mut self,
+        e: &Expr,
+        want: Kind,
+        const_only: bool,
+        what: &str,
+    ) -> Result<(), AlnError> {
+        let got = self.infer(e, const_only)?;
+        if got != want {
+            return self.error(
+                e.span,
+                format!(
+                    "{} must be {}, found a {} expression",
+                    what,
+                    want.name(),
+                    got.name()
+                ),
+            );
+        }
+        Ok(())
+    }
+
+    /// Kind of `e`. With `const_only`, only constants may appear (array sizes,
+    /// CONST values).
+    fn infer(&mut self, e: &Expr, const_only: bool) -> Result<Kind, AlnError> {
+        match &e.kind {
+            ExprKind::Number(_) => Ok(Kind::Num),
+            ExprKind::Bool(_) => Ok(Kind::Bool),
+            ExprKind::Var(name) => {
+                if self.sum_vars.iter().any(|v| v == name) {
+                    return Ok(Kind::Num);
+                }
+                match self.symbols.get(name).copied() {
+                    Some(Symbol::Const) => Ok(Kind::Num),
+                    Some(_) if const_only => {
+                        self.error(e.span, format!("`{}` is not a constant", name))
+                    }
+                    Some(Symbol::Input { ty, array }) | Some(Symbol::Output { ty, array }) => {
+                        if array {
+                            return self.error(
+                                e.span,
+                                format!("`{}` is an array; index it as `{}[i]`", name, name),
+                            );
+                        }
+                        match self.bound.get(name) {
+                            Some(_) => Ok(Kind::of(ty)),
+                            None if matches!(self.symbols[name], Symbol::Input { .. }) => {
+                                Ok(Kind::of(ty))
+                            }
+                            None => self
+                                .error(e.span, format!("OUTPUT `{}` is used before its LET", name)),
+                        }
+                    }
+                    Some(Symbol::Local(kind)) => match self.bound.get(name) {
+                        Some(_) => Ok(kind),
+                        None => self.error(e.span, format!("`{}` is used before its LET", name)),
+                    },
+                    None => self.error(
+                        e.span,
+                        format!(
+                            "unknown name `{}`; declare it with `EXTERN {};` if the caller binds it",
+                            name, name
+                        ),
+                    ),
+                }
+            }
+            ExprKind::Index { array, index } => {
+                if const_only {
+                    return self.error(e.span, format!("`{}` is not a constant", array.name));
+                }
+                self.expect_kind(index, Kind::Num, false, "array index")?;
+                match self.symbols.get(&array.name).copied() {
+                    Some(Symbol::Input { ty, array: true })
+                    | Some(Symbol::Output { ty, array: true }) => Ok(Kind::of(ty)),
+                    Some(_) => self.error(
+                        array.span,
+                        format!("`{}` is not an array and cannot be indexed", array.name),
+                    ),
+                    None => self.error(array.span, format!("unknown array `{}`", array.name)),
+                }
+            }
+            ExprKind::Unary { op, operand } => {
+                let want = match op {
+                    UnaryOp::Neg => Kind::Num,
+                    UnaryOp::Not => Kind::Bool,
+                };
+                self.expect_kind(operand, want, const_only, "operand")?;
+                Ok(want)
+            }
+            ExprKind::Binary { op, lhs, rhs } => {
+                let l = self.infer(lhs, const_only)?;
+                let r = self.infer(rhs, const_only)?;
+                let operand = if op.is_logical() {
+                    Kind::Bool
+                } else if matches!(op, BinaryOp::Eq | BinaryOp::Ne) {
+                    l
+                } else {
+                    Kind::Num
+                };
+                for (k, side) in [(l, lhs), (r, rhs)] {
+                    if k != operand {
+                        return self.error(
+                            side.span,
+                            format!(
+                                "operand of `{}` must be {}, found {}",
+                                op.as_str(),
+                                operand.name(),
+                                k.name()
+                            ),
+                        );
+                    }
+                }
+                Ok(if op.is_comparison() || op.is_logical() {
+                    Kind::Bool
+                } else {
+                    Kind::Num
+                })
+            }
+            ExprKind::Sum { var, lo, hi, body } => {
+                if self.symbols.contains_key(&var.name) || self.sum_vars.contains(&var.name) {
+                    return self.error(
+                        var.span,
+                        format!("SUM variable `{}` shadows another name", var.name),
+                    );
+                }
+                self.expect_kind(lo, Kind::Num, const_only, "SUM lower bound")?;
+                self.expect_kind(hi, Kind::Num, const_only, "SUM upper bound")?;
+                self.sum_vars.push(var.name.clone());
+                let res = self.expect_kind(body, Kind::Num, const_only, "SUM body");
+                self.sum_vars.pop();
+                res.map(|_| Kind::Num)
+            }
+            ExprKind::Call { args, .. } => {
+                for a in args {
+                    self.expect_kind(a, Kind::Num, const_only, "function argument")?;
+                }
+                Ok(Kind::Num)
+            }
+        }
+    }
+}
+
+/// Parse and check `.aln` source.
+pub fn parse_aln(src: &str) -> Result<AlnFile, AlnError> {
+    let mut parser = Parser {
+        src,
+        toks: tokenize(src)?,
+        pos: 0,
+    };
+    parser.file()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn contract(body: &str) -> Result<Contract, AlnError> {
+        let src = format!("CONTRACT C\n{}END;\n", body);
+        parse_aln(&src).map(|mut f| f.contracts.remove(0))
+    }
+
+    fn error(body: &str) -> String {
+        contract(body).unwrap_err().message
+    }
+
+    #[test]
+    fn test_extern_declares_a_constant() {
+        let c = contract(
+            "  EXTERN N;\n  EXTERN EPS;\n  INPUT x[N] : REAL;\n  \
+             INVARIANT Small: SUM(i IN 0..N-1) (x[i]) <= EPS;\n",
+        )
+        .unwrap();
+        let externs: Vec<&str> = c.externs.iter().map(|x| x.name.name.as_str()).collect();
+        assert_eq!(externs, vec!["N", "EPS"]);
+
+        let msg = error("  EXTERN N;\n  EXTERN N;\n");
+        assert!(msg.contains("declared twice"), "{}", msg);
+        let msg = error("  EXTERN N;\n  LET N = 1;\n");
+        assert!(msg.contains("cannot LET `N`"), "{}", msg);
+        let msg = error("  EXTERN N = 3;\n");
+        assert!(msg.contains("expected `;`"), "{}", msg);
+    }
+
+    #[test]
+    fn test_unknown_names_are_rejected() {
+        // A misspelled EXTERN must not become a new constant.
+        let src = "CONTRACT C\n  EXTERN EPS;\n  INPUT x : REAL;\n  INVARIANT Ok: x <= ESP;\nEND;\n";
+        let err = parse_aln(src).unwrap_err();
+        assert!(err.message.contains("unknown name `ESP`"), "{}", err);
+        assert_eq!((err.line, err.column), (4, 22));
+
+        let msg = error("  INPUT x[N] : REAL;\n");
+        assert!(msg.contains("unknown name `N`"), "{}", msg);
+        let msg = error("  INPUT x : REAL;\n  INVARIANT Ok: y[0] < x;\n");
+        assert!(msg.contains("unknown array `y`"), "{}", msg);
+    }
+
+    #[test]
+    fn test_type_and_binding_errors() {
+        let msg = error("  INPUT x : REAL;\n  INVARIANT Ok: x + 1;\n");
+        assert!(msg.contains("INVARIANT must be boolean"), "{}", msg);
+        let msg = error("  INPUT b : BOOL;\n  INVARIANT Ok: b < 1;\n");
+        assert!(msg.contains("operand of `<` must be numeric"), "{}", msg);
+        let msg = error("  INPUT x : REAL;\n  OUTPUT y : REAL;\n");
+        assert!(msg.contains("never bound by LET"), "{}", msg);
+        let msg = error("  INPUT x : REAL;\n  LET a = b;\n  LET b = x;\n");
+        assert!(msg.contains("unknown name `b`"), "{}", msg);
+        let msg = error("  INPUT x : REAL;\n  INVARIANT Ok: 0 < x < 1;\n");
+        assert!(msg.contains("cannot be chained"), "{}", msg);
+        let msg = error("  INPUT x : REAL;\n  INVARIANT Ok: x = 1;\n");
+        assert!(msg.contains("use `==`"), "{}", msg);
+        let msg = error("  INPUT x : REAL;\n  INVARIANT Ok: SQRT(x) > 0;\n");
+        assert!(msg.contains("unknown function `SQRT`"), "{}", msg);
+        let msg = error("  INPUT x : REAL;\n  INVARIANT Ok: MAX(x) > 0;\n");
+        assert!(msg.contains("takes 2 argument(s), found 1"), "{}", msg);
+        let msg = error("  INPUT x : REAL;\n  INPUT n : INT;\n  INPUT y[n] : REAL;\n");
+        assert!(msg.contains("`n` is not a constant"), "{}", msg);
+    }
+
+    #[test]
+    fn test_operator_precedence() {
+        let c = contract("  INPUT a : REAL;\n  LET v = a + 2 * a - 1;\n").unwrap();
+        // (a + (2 * a)) - 1
+        let ExprKind::Binary { op, lhs, .. } = &c.lets[0].value.kind else {
+            panic!("{:?}", c.lets[0].value);
+        };
+        assert_eq!(*op, BinaryOp::Sub);
+        let ExprKind::Binary { op, rhs, .. } = &lhs.kind else {
+            panic!("{:?}", lhs);
+        };
+        assert_eq!(*op, BinaryOp::Add);
+        assert!(matches!(
+            rhs.kind,
+            ExprKind::Binary {
+                op: BinaryOp::Mul,
+                ..
+            }
+        ));
+
+        let c = contract("  INPUT a : BOOL;\n  INPUT b : BOOL;\n  LET v = NOT a OR a AND b;\n")
+            .unwrap();
+        assert!(matches!(
+            c.lets[0].value.kind,
+            ExprKind::Binary {
+                op: BinaryOp::Or,
+                ..
+            }
+        ));
+    }
+}
@@ -0,0 +1,619 @@
+use crate::aln::ast::*;
+use crate::error::EcosafetyError;
+use crate::lca_gate::lca_ok;
+use crate::lyapunov::{compute_residual, is_admissible, ResidualState, RiskCoord};
+use crate::telemetry_shard::TelemetryRecord;
+use crate::types::LcaScenario;
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+
+/// Runtime value of an `.aln` name.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    Num(f64),
+    Bool(bool),
+    Array(Vec<Value>),
+}
+
+impl Value {
+    fn shape(&self) -> &'static str {
+        match self {
+            Value::Num(_) => "number",
+            Value::Bool(_) => "boolean",
+            Value::Array(_) => "array",
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Num(x) => write!(f, "{}", x),
+            Value::Bool(b) => write!(f, "{}", if *b { "TRUE" } else { "FALSE" }),
+            Value::Array(items) => {
+                f.write_str("[")?;
+                for (i, v) in items.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(", ")?;
+                    }
+                    write!(f, "{}", v)?;
+                }
+                f.write_str("]")
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum EvalError {
+    Unbound {
+        name: String,
+    },
+    Shape {
+        name: String,
+        expected: String,
+        found: &'static str,
+    },
+    ArrayLength {
+        name: String,
+        expected: usize,
+        found: usize,
+    },
+    IndexOutOfBounds {
+        array: String,
+        index: f64,
+        len: usize,
+        span: Span,
+    },
+    NotAnInteger {
+        value: f64,
+        span: Span,
+    },
+    DivisionByZero {
+        span: Span,
+    },
+}
+
+impl fmt::Display for EvalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EvalError::Unbound { name } => write!(f, "no value bound for `{}`", name),
+            EvalError::Shape {
+                name,
+                expected,
+                found,
+            } => write!(f, "`{}` must be {}, found {}", name, expected, found),
+            EvalError::ArrayLength {
+                name,
+                expected,
+                found,
+            } => write!(
+                f,
+                "`{}` must have {} elements, found {}",
+                name, expected, found
+            ),
+            EvalError::IndexOutOfBounds {
+                array, index, len, ..
+            } => write!(
+                f,
+                "index {} out of bounds for `{}` of length {}",
+                index, array, len
+            ),
+            EvalError::NotAnInteger { value, .. } => {
+                write!(f, "expected an integer, found {}", value)
+            }
+            EvalError::DivisionByZero { .. } => write!(f, "division by zero"),
+        }
+    }
+}
+
+impl Error for EvalError {}
+
+/// Values for a contract's INPUTs and EXTERN constants.
+#[derive(Clone, Debug, Default)]
+pub struct Bindings {
+    values: BTreeMap<String, Value>,
+}
+
+impl Bindings {
+    pub fn set(&mut self, name: &str, value: Value) -> &mut Self {
+        self.values.insert(name.to_string(), value);
+        self
+    }
+
+    pub fn num(&mut self, name: &str, x: f64) -> &mut Self {
+        self.set(name, Value::Num(x))
+    }
+
+    pub fn array(&mut self, name: &str, xs: &[f64]) -> &mut Self {
+        self.set(
+            name,
+            Value::Array(xs.iter().map(|x| Value::Num(*x)).collect()),
+        )
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Value> {
+        self.values.get(name)
+    }
+
+    /// Residual inputs of `CybocinderLyapunov`: `r_x`, `w`, `V_prev` and
+    /// `NUM_CHANNELS`.
+    pub fn from_risk_coords(coords: &[RiskCoord], v_prev: f64) -> Self {
+        let mut b = Bindings::default();
+        b.array("r_x", &coords.iter().map(|c| c.r).collect::<Vec<_>>())
+            .array("w", &coords.iter().map(|c| c.w).collect::<Vec<_>>())
+            .num("V_prev", v_prev)
+            .num("NUM_CHANNELS", coords.len() as f64);
+        b
+    }
+
+    /// Residual inputs from the telemetry rows of one timestamp, one row per
+    /// channel; channels must be 0..N without gaps or duplicates.
+    pub fn from_telemetry(records: &[TelemetryRecord], v_prev: f64) -> Result<Self, EvalError> {
+        let mut by_channel: BTreeMap<u32, &TelemetryRecord> = BTreeMap::new();
+        for rec in records {
+            if by_channel.insert(rec.channel, rec).is_some() {
+                return Err(EvalError::Shape {
+                    name: "r_x".to_string(),
+                    expected: format!("one telemetry row for channel {}", rec.channel),
+                    found: "duplicate rows",
+                });
+            }
+        }
+        for (expected, channel) in by_channel.keys().enumerate() {
+            if *channel as usize != expected {
+                return Err(EvalError::Unbound {
+                    name: format!("r_x[{}]", expected),
+                });
+            }
+        }
+        let coords: Vec<RiskCoord> = by_channel
+            .values()
+            .map(|rec| RiskCoord {
+                r: rec.risk_r,
+                w: rec.weight_w,
+            })
+            .collect();
+        Ok(Bindings::from_risk_coords(&coords, v_prev))
+    }
+
+    /// Scalar fields of a single telemetry row: `value`, `risk_r`, `weight_w`,
+    /// `V_t`, `legal_ok`, `gold_ok` and `gate_safety_ok`.
+    pub fn from_telemetry_record(rec: &TelemetryRecord) -> Self {
+        let mut b = Bindings::default();
+        b.num("value", rec.value)
+            .num("risk_r", rec.risk_r)
+            .num("weight_w", rec.weight_w)
+            .num("V_t", rec.v_t)
+            .set("legal_ok", Value::Bool(rec.legal_ok))
+            .set("gold_ok", Value::Bool(rec.gold_ok))
+            .set("gate_safety_ok", Value::Bool(rec.gate_safety_ok));
+        b
+    }
+
+    /// Inputs of `LcaDeploymentGate`: `GWP_base` and `GWP_cybo`, plus
+    /// `<category>_base` / `<category>_cybo` for every other impact.
+    pub fn from_lca_pair(base: &LcaScenario, cybo: &LcaScenario) -> Self {
+        let mut b = Bindings::default();
+        b.num("GWP_base", base.gwp_kg_co2eq)
+            .num("GWP_cybo", cybo.gwp_kg_co2eq);
+        for (suffix, s) in [("base", base), ("cybo", cybo)] {
+            for (cat, x) in &s.other_impacts {
+                b.num(&format!("{}_{}", cat, suffix), *x);
+            }
+        }
+        b
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct InvariantResult {
+    pub name: String,
+    pub holds: bool,
+}
+
+/// Computed LET/OUTPUT values and invariant outcomes of one evaluation.
+#[derive(Clone, Debug)]
+pub struct Evaluation {
+    pub contract: String,
+    pub values: BTreeMap<String, Value>,
+    pub invariants: Vec<InvariantResult>,
+}
+
+impl Evaluation {
+    pub fn all_hold(&self) -> bool {
+        self.invariants.iter().all(|i| i.holds)
+    }
+
+    pub fn num(&self, name: &str) -> Option<f64> {
+        match self.values.get(name) {
+            Some(Value::Num(x)) => Some(*x),
+            _ => None,
+        }
+    }
+}
+
+struct Env<'a> {
+    bindings: &'a Bindings,
+    locals: BTreeMap<String, Value>,
+    sum_vars: Vec<(String, f64)>,
+}
+
+impl Env<'_> {
+    fn lookup(&self, name: &str) -> Result<&Value, EvalError> {
+        self.locals
+            .get(name)
+            .or_else(|| self.bindings.get(name))
+            .ok_or_else(|| EvalError::Unbound {
+                name: name.to_string(),
+            })
+    }
+
+    fn num(&mut self, e: &Expr) -> Result<f64, EvalError> {
+        match self.eval(e)? {
+            Value::Num(x) => Ok(x),
+            other => Err(EvalError::Shape {
+                name: "expression".to_string(),
+                expected: "a number".to_string(),
+                found: other.shape(),
+            }),
+        }
+    }
+
+    fn boolean(&mut self, e: &Expr) -> Result<bool, EvalError> {
+        match self.eval(e)? {
+            Value::Bool(b) => Ok(b),
+            other => Err(EvalError::Shape {
+                name: "expression".to_string(),
+                expected: "a boolean".to_string(),
+                found: other.shape(),
+            }),
+        }
+    }
+
+    fn integer(&mut self, e: &Expr) -> Result<i64, EvalError> {
+        let x = self.num(e)?;
+        if x.fract() != 0.0 || !x.is_finite() {
+            return Err(EvalError::NotAnInteger {
+                value: x,
+                span: e.span,
+            });
+        }
+        Ok(x as i64)
+    }
+
+    fn eval(&mut self, e: &Expr) -> Result<Value, EvalError> {
+        Ok(match &e.kind {
+            ExprKind::Number(x) => Value::Num(*x),
+            ExprKind::Bool(b) => Value::Bool(*b),
+            ExprKind::Var(name) => {
+                if let Some((_, x)) = self.sum_vars.iter().rev().find(|(n, _)| n == name) {
+                    return Ok(Value::Num(*x));
+                }
+                self.lookup(name)?.clone()
+            }
+            ExprKind::Index { array, index } => {
+                let i = self.integer(index)?;
+                let items = match self.lookup(&array.name)? {
+                    Value::Array(items) => items,
+                    other => {
+                        return Err(EvalError::Shape {
+                            name: array.name.clone(),
+                            expected: "an array".to_string(),
+                            found: other.shape(),
+                        })
+                    }
+                };
+                if i < 0 || i as usize >= items.len() {
+                    return Err(EvalError::IndexOutOfBounds {
+                        array: array.name.clone(),
+                        index: i as f64,
+                        len: items.len(),
+                        span: e.span,
+                    });
+                }
+                items[i as usize].clone()
+            }
+            ExprKind::Unary { op, operand } => match op {
+                UnaryOp::Neg => Value::Num(-self.num(operand)?),
+                UnaryOp::Not => Value::Bool(!self.boolean(operand)?),
+            },
+            ExprKind::Binary { op, lhs, rhs } => match op {
+                BinaryOp::And => Value::Bool(self.boolean(lhs)? && self.boolean(rhs)?),
+                BinaryOp::Or => Value::Bool(self.boolean(lhs)? || self.boolean(rhs)?),
+                BinaryOp::Eq | BinaryOp::Ne => {
+                    let eq = self.eval(lhs)? == self.eval(rhs)?;
+                    Value::Bool(if *op == BinaryOp::Eq { eq } else { !eq })
+                }
+                _ => {
+                    let a = self.num(lhs)?;
+                    let b = self.num(rhs)?;
+                    match op {
+                        BinaryOp::Add => Value::Num(a + b),
+                        BinaryOp::Sub => Value::Num(a - b),
+                        BinaryOp::Mul => Value::Num(a * b),
+                        BinaryOp::Div => {
+                            if b == 0.0 {
+                                return Err(EvalError::DivisionByZero { span: e.span });
+                            }
+                            Value::Num(a / b)
+                        }
+                        BinaryOp::Lt => Value::Bool(a < b),
+                        BinaryOp::Le => Value::Bool(a <= b),
+                        BinaryOp::Gt => Value::Bool(a > b),
+                        BinaryOp::Ge => Value::Bool(a >= b),
+                        _ => unreachable!("logical and equality operators handled above"),
+                    }
+                }
+            },
+            ExprKind::Sum { var, lo, hi, body } => {
+                let lo = self.integer(lo)?;
+                let hi = self.integer(hi)?;
+                let mut total = 0.0;
+                for i in lo..=hi {
+                    self.sum_vars.push((var.name.clone(), i as f64));
+                    let term = self.num(body);
+                    self.sum_vars.pop();
+                    total += term?;
+                }
+                Value::Num(total)
+            }
+            ExprKind::Call { func, args } => {
+                let a = self.num(&args[0])?;
+                Value::Num(match func {
+                    Builtin::Abs => a.abs(),
+                    Builtin::Clip01 => a.clamp(0.0, 1.0),
+                    Builtin::Min => a.min(self.num(&args[1])?),
+                    Builtin::Max => a.max(self.num(&args[1])?),
+                })
+            }
+        })
+    }
+}
+
+/// Evaluate a checked contract: CONSTs, then INPUT shape checks (array
+/// lengths must equal their declared size), then LETs in source order, then
+/// every INVARIANT.
+pub fn evaluate(contract: &Contract, bindings: &Bindings) -> Result<Evaluation, EvalError> {
+    let mut env = Env {
+        bindings,
+        locals: BTreeMap::new(),
+        sum_vars: Vec::new(),
+    };
+
+    for x in &contract.externs {
+        match env.lookup(&x.name.name)? {
+            Value::Num(_) => {}
+            other => {
+                return Err(EvalError::Shape {
+                    name: x.name.name.clone(),
+                    expected: "a number".to_string(),
+                    found: other.shape(),
+                })
+            }
+        }
+    }
+    for c in &contract.consts {
+        let x = env.num(&c.value)?;
+        env.locals.insert(c.name.name.clone(), Value::Num(x));
+    }
+
+    for d in &contract.inputs {
+        let value = env.lookup(&d.name.name)?.clone();
+        let scalar_ok = |v: &Value| match v {
+            Value::Num(_) => d.ty.is_numeric(),
+            Value::Bool(_) => !d.ty.is_numeric(),
+            Value::Array(_) => false,
+        };
+        let ok = match (&d.size, &value) {
+            (Some(size), Value::Array(items)) => {
+                let len = env.integer(size)?;
+                if len < 0 || items.len() != len as usize {
+                    return Err(EvalError::ArrayLength {
+                        name: d.name.name.clone(),
+                        expected: len.max(0) as usize,
+                        found: items.len(),
+                    });
+                }
+                items.iter().all(scalar_ok)
+            }
+            (None, v) => scalar_ok(v),
+            _ => false,
+        };
+        if !ok {
+            return Err(EvalError::Shape {
+                name: d.name.name.clone(),
+                expected: match d.size {
+                    Some(_) => format!("an array of {}", d.ty),
+                    None => format!("a {}", d.ty),
+                },
+                found: value.shape(),
+            });
+        }
+    }
+
+    let mut values = BTreeMap::new();
+    for l in &contract.lets {
+        let v = env.eval(&l.value)?;
+        env.locals.insert(l.name.name.clone(), v.clone());
+        values.insert(l.name.name.clone(), v);
+    }
+
+    let invariants = contract
+        .invariants
+        .iter()
+        .map(|inv| {
+            Ok(InvariantResult {
+                name: inv.name.name.clone(),
+                holds: env.boolean(&inv.expr)?,
+            })
+        })
+        .collect::<Result<_, EvalError>>()?;
+
+    Ok(Evaluation {
+        contract: contract.name.name.clone(),
+        values,
+        invariants,
+    })
+}
+
+/// The generated code and the interpreted contract disagreed, or the contract
+/// could not be evaluated on inputs the generated code accepted.
+#[derive(Clone, Debug)]
+pub struct CrossCheckAlarm {
+    pub contract: String,
+    pub quantity: String,
+    pub rust: String,
+    pub aln: String,
+}
+
+impl fmt::Display for CrossCheckAlarm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "contract {} disagrees on {}: rust={} aln={}",
+            self.contract, self.quantity, self.rust, self.aln
+        )
+    }
+}
+
+/// Runs `compute_residual` / `lca_ok` and the matching `.aln` contracts side
+/// by side. The Rust result is always what the caller gets back; every
+/// disagreement is recorded in `alarms`.
+///
+/// Both Rust functions evaluate the code `build.rs` generated from `spec/`,
+/// so this no longer checks a hand-written implementation against the spec.
+/// What it compares is the generated code against the interpreter, on the
+/// contracts passed to `new`: an alarm means the two evaluators disagree on
+/// the language, or that those contracts differ from the specs the binary
+/// was built from.
+#[derive(Clone, Debug)]
+pub struct CrossChecker {
+    pub residual_contract: Contract,
+    pub lca_contract: Contract,
+    pub eps: f64,
+    /// Allowed |V_rust − V_aln| before an alarm is raised.
+    pub tolerance: f64,
+    pub alarms: Vec<CrossCheckAlarm>,
+}
+
+impl CrossChecker {
+    pub fn new(residual_contract: Contract, lca_contract: Contract, eps: f64) -> Self {
+        CrossChecker {
+            residual_contract,
+            lca_contract,
+            eps,
+            tolerance: 1e-9,
+            alarms: Vec::new(),
+        }
+    }
+
+    fn alarm(&mut self, lca: bool, quantity: &str, rust: String, aln: String) {
+        let contract = if lca {
+            &self.lca_contract
+        } else {
+            &self.residual_contract
+        };
+        self.alarms.push(CrossCheckAlarm {
+            contract: contract.name.name.clone(),
+            quantity: quantity.to_string(),
+            rust,
+            aln,
+        });
+    }
+
+    /// `compute_residual` plus the admissibility of `V_prev → V`, checked
+    /// against the residual contract's `V_next` and invariants.
+    pub fn residual(
+        &mut self,
+        coords: &[RiskCoord],
+        v_prev: f64,
+    ) -> Result<ResidualState, EcosafetyError> {
+        let state = compute_residual(coords)?;
+        let admissible = is_admissible(v_prev, state.v, self.eps);
+
+        let mut bindings = Bindings::from_risk_coords(coords, v_prev);
+        bindings.num("EPS", self.eps);
+        match evaluate(&self.residual_contract, &bindings) {
+            Ok(eval) => {
+                match eval.num("V_next") {
+                    Some(v) if (v - state.v).abs() <= self.tolerance => {}
+                    other => self.alarm(
+                        false,
+                        "V_next",
+                        state.v.to_string(),
+                        other.map_or("missing".to_string(), |v| v.to_string()),
+                    ),
+                }
+                if eval.all_hold() != admissible {
+                    self.alarm(
+                        false,
+                        "admissibility",
+                        admissible.to_string(),
+                        eval.all_hold().to_string(),
+                    );
+                }
+            }
+            Err(e) => self.alarm(false, "evaluation", state.v.to_string(), e.to_string()),
+        }
+        Ok(state)
+    }
+
+    /// `lca_ok`, checked against the LCA contract's invariants.
+    pub fn lca_ok(
+        &mut self,
+        base: &LcaScenario,
+        cybo: &LcaScenario,
+    ) -> Result<bool, EcosafetyError> {
+        let ok = lca_ok(base, cybo)?;
+        match evaluate(&self.lca_contract, &Bindings::from_lca_pair(base, cybo)) {
+            Ok(eval) if eval.all_hold() == ok => {}
+            Ok(eval) => self.alarm(true, "lca_ok", ok.to_string(), eval.all_hold().to_string()),
+            Err(e) => self.alarm(true, "evaluation", ok.to_string(), e.to_string()),
+        }
+        Ok(ok)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aln::parse_aln;
+
+    const CORRIDORS: &str = include_str!("../../spec/corridors.cybo.aln");
+    const LCA_GATES: &str = include_str!("../../spec/lca-gates.cybo.aln");
+
+    fn coords() -> Vec<RiskCoord> {
+        vec![RiskCoord { r: 0.5, w: 0.4 }, RiskCoord { r: 0.25, w: 0.6 }]
+    }
+
+    #[test]
+    fn test_evaluate_residual_contract() {
+        let file = parse_aln(CORRIDORS).unwrap();
+        let mut b = Bindings::from_risk_coords(&coords(), 0.3);
+        b.num("EPS", 0.0);
+        let eval = evaluate(&file.contracts[0], &b).unwrap();
+        assert!((eval.num("V_next").unwrap() - 0.35).abs() < 1e-12);
+        assert!(!eval.all_hold());
+
+        b.num("V_prev", 0.35);
+        assert!(evaluate(&file.contracts[0], &b).unwrap().all_hold());
+    }
+
+    #[test]
+    fn test_cross_check_alarms_on_disagreement() {
+        let lca = parse_aln(LCA_GATES).unwrap().contracts.remove(0);
+        let good = parse_aln(CORRIDORS).unwrap().contracts.remove(0);
+        let mut checker = CrossChecker::new(good, lca.clone(), 0.01);
+        checker.residual(&coords(), 0.3).unwrap();
+        checker.residual(&coords(), 0.5).unwrap();
+        assert!(checker.alarms.is_empty(), "{:?}", checker.alarms);
+
+        let unweighted = CORRIDORS.replace("w[i] * r_x[i]", "r_x[i]");
+        let bad = parse_aln(&unweighted).unwrap().contracts.remove(0);
+        let mut checker = CrossChecker::new(bad, lca, 0.01);
+        checker.residual(&coords(), 0.5).unwrap();
+        assert!(checker.alarms.iter().any(|a| a.quantity == "V_next"));
+    }
+}
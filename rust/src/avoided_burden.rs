@@ -0,0 +1,291 @@
+use crate::error::EcosafetyError;
+use crate::types::{FunctionalUnit, LcaScenario, ScenarioMode};
+
+/// Physical constants of the avoided-burden model that are not carried by
+/// the LCA shard itself.
+#[derive(Clone, Debug)]
+pub struct AvoidedBurdenModel {
+    /// Lower heating value of as-received MSW.
+    pub msw_lhv_kwh_per_ton: f64,
+    /// Ferrous + non-ferrous metal recovered from bottom ash per ton fired.
+    pub metal_recovered_kg_per_ton: f64,
+}
+
+impl Default for AvoidedBurdenModel {
+    fn default() -> Self {
+        // ~10 GJ/t LHV for mixed MSW; ~25 kg/t metals from bottom ash.
+        AvoidedBurdenModel {
+            msw_lhv_kwh_per_ton: 2800.0,
+            metal_recovered_kg_per_ton: 25.0,
+        }
+    }
+}
+
+/// Recomputed GWP for one scenario, per its functional unit (kg CO2eq).
+#[derive(Clone, Debug)]
+pub struct GwpBreakdown {
+    pub scenario_id: String,
+    pub treatment_kgco2eq: f64,
+    pub energy_credit_kgco2eq: f64,
+    pub material_credit_kgco2eq: f64,
+    pub net_kgco2eq: f64,
+}
+
+fn required(
+    s: &LcaScenario,
+    field: &'static str,
+    value: Option<f64>,
+) -> Result<f64, EcosafetyError> {
+    value.ok_or_else(|| EcosafetyError::MissingLcaParameter {
+        scenario_id: s.scenario_id.clone(),
+        field,
+    })
+}
+
+fn fraction(
+    s: &LcaScenario,
+    field: &'static str,
+    value: Option<f64>,
+) -> Result<f64, EcosafetyError> {
+    let value = required(s, field, value)?;
+    if (0.0..=1.0).contains(&value) {
+        Ok(value)
+    } else {
+        Err(EcosafetyError::InvalidLcaParameter {
+            scenario_id: s.scenario_id.clone(),
+            field,
+            value,
+        })
+    }
+}
+
+fn breakdown(
+    s: &LcaScenario,
+    treatment: f64,
+    energy_credit: f64,
+    material_credit: f64,
+) -> GwpBreakdown {
+    GwpBreakdown {
+        scenario_id: s.scenario_id.clone(),
+        treatment_kgco2eq: treatment,
+        energy_credit_kgco2eq: energy_credit,
+        material_credit_kgco2eq: material_credit,
+        net_kgco2eq: treatment - energy_credit - material_credit,
+    }
+}
+
+/// Recompute net GWP from the avoided-burden parameters of a scenario.
+///
+/// Per ton of MSW collected, the non-recycled fraction `1 - recycling_rate`
+/// is treated: landfilled at `landfill_ref_GWP` (STATUS_QUO) or fired at
+/// `furnace_direct_kgco2eq_per_ton` (CYBOCINDER). Recovered energy
+/// (LHV · efficiency) is credited at the grid intensity and, for
+/// cybocinders, bottom-ash metal at `avoided_virgin_metal`. The result is
+/// then expressed per functional unit:
+///
+/// - MSW_TON: net burden per ton.
+/// - ENERGY_MWH: STATUS_QUO is the grid itself; CYBOCINDER is the burden of
+///   the tons needed to deliver 1 MWh, excluding the energy credit.
+/// - RESOURCE_KG: STATUS_QUO is virgin metal production; CYBOCINDER is the
+///   burden of the tons needed to recover 1 kg, excluding the metal credit.
+///
+/// A parameter the case needs but the scenario lacks is `MissingLcaParameter`.
+pub fn recompute_gwp(
+    s: &LcaScenario,
+    model: &AvoidedBurdenModel,
+    furnace_direct_kgco2eq_per_ton: f64,
+) -> Result<GwpBreakdown, EcosafetyError> {
+    if s.grid_gco2_per_kwh < 0.0 {
+        return Err(EcosafetyError::InvalidLcaParameter {
+            scenario_id: s.scenario_id.clone(),
+            field: "grid_gCO2_per_kWh",
+            value: s.grid_gco2_per_kwh,
+        });
+    }
+    let cybo = s.mode == ScenarioMode::Cybocinder;
+    match (s.functional_unit, cybo) {
+        (FunctionalUnit::EnergyMwh, false) => {
+            return Ok(breakdown(s, s.grid_gco2_per_kwh, 0.0, 0.0))
+        }
+        (FunctionalUnit::ResourceKg, false) => {
+            let virgin = required(
+                s,
+                "avoided_virgin_metal_kgCO2eq_per_kg",
+                s.avoided_virgin_metal_kgco2eq_per_kg,
+            )?;
+            return Ok(breakdown(s, virgin, 0.0, 0.0));
+        }
+        _ => {}
+    }
+
+    let recycling = fraction(s, "recycling_rate", s.recycling_rate)?;
+    let efficiency = fraction(
+        s,
+        "energy_recovery_efficiency",
+        s.energy_recovery_efficiency,
+    )?;
+    let residual = 1.0 - recycling;
+    let grid_kg_per_kwh = s.grid_gco2_per_kwh / 1000.0;
+
+    // Per ton of MSW collected.
+    let treatment_per_ton = if cybo {
+        residual * furnace_direct_kgco2eq_per_ton
+    } else {
+        let landfill = required(
+            s,
+            "landfill_ref_GWP_kgCO2eq_per_ton",
+            s.landfill_ref_gwp_kgco2_per_ton,
+        )?;
+        residual * landfill
+    };
+    let energy_kwh_per_ton = residual * model.msw_lhv_kwh_per_ton * efficiency;
+    let energy_credit_per_ton = energy_kwh_per_ton * grid_kg_per_kwh;
+    let metal_kg_per_ton = if cybo {
+        residual * model.metal_recovered_kg_per_ton
+    } else {
+        0.0
+    };
+    // RESOURCE_KG excludes the metal credit, so it needs no virgin-metal figure.
+    let material_credit_per_ton = if cybo && s.functional_unit != FunctionalUnit::ResourceKg {
+        let virgin = required(
+            s,
+            "avoided_virgin_metal_kgCO2eq_per_kg",
+            s.avoided_virgin_metal_kgco2eq_per_kg,
+        )?;
+        metal_kg_per_ton * virgin
+    } else {
+        0.0
+    };
+
+    Ok(match s.functional_unit {
+        FunctionalUnit::MswTon => breakdown(
+            s,
+            treatment_per_ton,
+            energy_credit_per_ton,
+            material_credit_per_ton,
+        ),
+        FunctionalUnit::EnergyMwh => {
+            let mwh_per_ton = energy_kwh_per_ton / 1000.0;
+            if mwh_per_ton <= 0.0 {
+                return Err(EcosafetyError::InvalidLcaParameter {
+                    scenario_id: s.scenario_id.clone(),
+                    field: "energy_recovery_efficiency",
+                    value: efficiency,
+                });
+            }
+            breakdown(
+                s,
+                treatment_per_ton / mwh_per_ton,
+                0.0,
+                material_credit_per_ton / mwh_per_ton,
+            )
+        }
+        FunctionalUnit::ResourceKg => {
+            if metal_kg_per_ton <= 0.0 {
+                return Err(EcosafetyError::InvalidLcaParameter {
+                    scenario_id: s.scenario_id.clone(),
+                    field: "recycling_rate",
+                    value: recycling,
+                });
+            }
+            breakdown(
+                s,
+                treatment_per_ton / metal_kg_per_ton,
+                energy_credit_per_ton / metal_kg_per_ton,
+                0.0,
+            )
+        }
+    })
+}
+
+/// Stated vs recomputed GWP for one scenario.
+#[derive(Clone, Debug)]
+pub struct GwpAudit {
+    pub scenario_id: String,
+    pub stated_kgco2eq: f64,
+    pub recomputed: GwpBreakdown,
+    /// |stated − recomputed| in kg CO2eq.
+    pub absolute_deviation_kgco2eq: f64,
+    /// |stated − recomputed| / |stated|; `None` when the stated GWP is 0.
+    pub relative_deviation: Option<f64>,
+    pub flagged: bool,
+}
+
+/// Compare each scenario's stated GWP against `recompute_gwp`. A scenario
+/// is flagged when |stated − recomputed| exceeds `rel_tolerance · |stated|`.
+/// A stated GWP of exactly 0 has no scale: it is flagged when the absolute
+/// difference exceeds `abs_tolerance_kgco2eq` instead.
+pub fn audit_gwp(
+    scenarios: &[LcaScenario],
+    model: &AvoidedBurdenModel,
+    furnace_direct_kgco2eq_per_ton: f64,
+    rel_tolerance: f64,
+    abs_tolerance_kgco2eq: f64,
+) -> Result<Vec<GwpAudit>, EcosafetyError> {
+    scenarios
+        .iter()
+        .map(|s| {
+            let recomputed = recompute_gwp(s, model, furnace_direct_kgco2eq_per_ton)?;
+            let diff = (s.gwp_kg_co2eq - recomputed.net_kgco2eq).abs();
+            let scale = s.gwp_kg_co2eq.abs();
+            let relative_deviation = (scale > 0.0).then(|| diff / scale);
+            let flagged = match relative_deviation {
+                Some(rel) => rel > rel_tolerance,
+                None => diff > abs_tolerance_kgco2eq,
+            };
+            Ok(GwpAudit {
+                scenario_id: s.scenario_id.clone(),
+                stated_kgco2eq: s.gwp_kg_co2eq,
+                recomputed,
+                absolute_deviation_kgco2eq: diff,
+                relative_deviation,
+                flagged,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    fn cybo(stated: f64) -> LcaScenario {
+        LcaScenario {
+            scenario_id: "PHX-CYBO".to_string(),
+            region_id: "PHX".to_string(),
+            functional_unit: FunctionalUnit::MswTon,
+            mode: ScenarioMode::Cybocinder,
+            gwp_kg_co2eq: stated,
+            grid_gco2_per_kwh: 400.0,
+            landfill_ref_gwp_kgco2_per_ton: None,
+            avoided_virgin_metal_kgco2eq_per_kg: Some(2.0),
+            energy_recovery_efficiency: Some(0.2),
+            recycling_rate: Some(0.25),
+            other_impacts: BTreeMap::new(),
+        }
+    }
+
+    #[test]
+    fn test_audit_flags_stated_gwp_off_the_recomputed_value() {
+        let model = AvoidedBurdenModel::default();
+        // 0.75 t fired at 350, minus 420 kWh at 0.4 kg/kWh, minus 18.75 kg
+        // metal at 2 kg/kg: 262.5 − 168 − 37.5 = 57.
+        let b = recompute_gwp(&cybo(0.0), &model, 350.0).unwrap();
+        assert!((b.net_kgco2eq - 57.0).abs() < 1e-9);
+
+        let audit = audit_gwp(&[cybo(60.0)], &model, 350.0, 0.1, 1.0).unwrap();
+        assert!((audit[0].relative_deviation.unwrap() - 0.05).abs() < 1e-9);
+        assert!((audit[0].absolute_deviation_kgco2eq - 3.0).abs() < 1e-9);
+        assert!(!audit[0].flagged);
+        // The absolute tolerance does not apply when there is a scale.
+        assert!(audit_gwp(&[cybo(60.0)], &model, 350.0, 0.04, 100.0).unwrap()[0].flagged);
+
+        // Stated 0: no relative deviation; judged on the absolute one.
+        let audit = audit_gwp(&[cybo(0.0)], &model, 350.0, 0.1, 60.0).unwrap();
+        assert_eq!(audit[0].relative_deviation, None);
+        assert!((audit[0].absolute_deviation_kgco2eq - 57.0).abs() < 1e-9);
+        assert!(!audit[0].flagged);
+        assert!(audit_gwp(&[cybo(0.0)], &model, 350.0, 10.0, 50.0).unwrap()[0].flagged);
+    }
+}
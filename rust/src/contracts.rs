@@ -0,0 +1,184 @@
+//! Typed Rust APIs generated by `build.rs` from the CONTRACT blocks in
+//! `spec/*.aln`: one module per contract (e.g. `cybocinder_lyapunov`,
+//! `lca_deployment_gate`) with `Constants`, `Inputs`, `Outputs`, a
+//! `check_<invariant>()` per INVARIANT and `check()` for all of them.
+//! Editing a spec changes these signatures, so callers stop compiling
+//! instead of silently drifting from it; `Constants` and `Inputs` have no
+//! `Default`, so a new field cannot be left at zero by accident.
+
+use std::error::Error;
+use std::fmt;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ContractError {
+    ArrayLength {
+        name: &'static str,
+        expected: f64,
+        found: usize,
+    },
+    IndexOutOfBounds {
+        name: &'static str,
+        index: f64,
+        len: usize,
+    },
+    NotAnInteger {
+        value: f64,
+    },
+    DivisionByZero,
+}
+
+impl fmt::Display for ContractError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ContractError::ArrayLength {
+                name,
+                expected,
+                found,
+            } => write!(
+                f,
+                "`{}` must have {} elements, found {}",
+                name, expected, found
+            ),
+            ContractError::IndexOutOfBounds { name, index, len } => write!(
+                f,
+                "index {} out of bounds for `{}` of length {}",
+                index, name, len
+            ),
+            ContractError::NotAnInteger { value } => {
+                write!(f, "expected an integer, found {}", value)
+            }
+            ContractError::DivisionByZero => write!(f, "division by zero"),
+        }
+    }
+}
+
+impl Error for ContractError {}
+
+// Runtime helpers for the generated code. Which of them are used depends on
+// the specs, hence the `dead_code` allowances.
+
+#[allow(dead_code)]
+fn int(x: f64) -> Result<i64, ContractError> {
+    if !x.is_finite() || x.fract() != 0.0 {
+        return Err(ContractError::NotAnInteger { value: x });
+    }
+    Ok(x as i64)
+}
+
+#[allow(dead_code)]
+fn check_len(name: &'static str, len: usize, expected: f64) -> Result<(), ContractError> {
+    match int(expected)? {
+        n if n >= 0 && n as usize == len => Ok(()),
+        _ => Err(ContractError::ArrayLength {
+            name,
+            expected,
+            found: len,
+        }),
+    }
+}
+
+#[allow(dead_code)]
+fn at<T: Copy>(xs: &[T], index: f64, name: &'static str) -> Result<T, ContractError> {
+    let i = int(index)?;
+    if i < 0 || i as usize >= xs.len() {
+        return Err(ContractError::IndexOutOfBounds {
+            name,
+            index,
+            len: xs.len(),
+        });
+    }
+    Ok(xs[i as usize])
+}
+
+#[allow(dead_code)]
+fn div(a: f64, b: f64) -> Result<f64, ContractError> {
+    if b == 0.0 {
+        return Err(ContractError::DivisionByZero);
+    }
+    Ok(a / b)
+}
+
+/// `SUM(i IN lo..hi) (body)`, both bounds inclusive.
+#[allow(dead_code)]
+fn sum(
+    lo: f64,
+    hi: f64,
+    body: impl Fn(f64) -> Result<f64, ContractError>,
+) -> Result<f64, ContractError> {
+    let mut total = 0.0;
+    for i in int(lo)?..=int(hi)? {
+        total += body(i as f64)?;
+    }
+    Ok(total)
+}
+
+include!(concat!(env!("OUT_DIR"), "/aln_contracts.rs"));
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lca_gate::lca_ok;
+    use crate::lyapunov::{compute_residual, is_admissible, RiskCoord};
+    use crate::types::{FunctionalUnit, LcaScenario, ScenarioMode};
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn test_generated_residual_matches_lyapunov() {
+        let coords = [RiskCoord { r: 0.5, w: 0.4 }, RiskCoord { r: 0.25, w: 0.6 }];
+        let k = cybocinder_lyapunov::Constants {
+            eps: 0.01,
+            num_channels: coords.len() as f64,
+        };
+        for v_prev in [0.2, 0.34, 0.5] {
+            let inp = cybocinder_lyapunov::Inputs {
+                r_x: coords.iter().map(|c| c.r).collect(),
+                w: coords.iter().map(|c| c.w).collect(),
+                v_prev,
+            };
+            let report = cybocinder_lyapunov::check(&k, &inp).unwrap();
+            let v = compute_residual(&coords).unwrap().v;
+            assert!((report.outputs.v_next - v).abs() < 1e-12);
+            assert_eq!(
+                report.non_increasing_residual,
+                is_admissible(v_prev, v, k.eps)
+            );
+        }
+
+        let short = cybocinder_lyapunov::Inputs {
+            r_x: vec![0.5],
+            w: vec![0.4, 0.6],
+            v_prev: 0.3,
+        };
+        assert!(matches!(
+            cybocinder_lyapunov::check(&k, &short),
+            Err(ContractError::ArrayLength { name: "r_x", .. })
+        ));
+    }
+
+    #[test]
+    fn test_generated_lca_gate_matches_lca_ok() {
+        let scenario = |mode, gwp| LcaScenario {
+            scenario_id: format!("{:?}", mode),
+            region_id: "PHX".to_string(),
+            functional_unit: FunctionalUnit::MswTon,
+            mode,
+            gwp_kg_co2eq: gwp,
+            grid_gco2_per_kwh: 400.0,
+            landfill_ref_gwp_kgco2_per_ton: None,
+            avoided_virgin_metal_kgco2eq_per_kg: None,
+            energy_recovery_efficiency: None,
+            recycling_rate: None,
+            other_impacts: BTreeMap::new(),
+        };
+        let base = scenario(ScenarioMode::StatusQuo, 500.0);
+        for gwp in [300.0, 500.0, 700.0] {
+            let cybo = scenario(ScenarioMode::Cybocinder, gwp);
+            let inp = lca_deployment_gate::Inputs {
+                gwp_base: base.gwp_kg_co2eq,
+                gwp_cybo: cybo.gwp_kg_co2eq,
+            };
+            let report = lca_deployment_gate::check(&lca_deployment_gate::Constants {}, &inp).unwrap();
+            assert_eq!(report.primary_lca, lca_ok(&base, &cybo).unwrap());
+        }
+    }
+}
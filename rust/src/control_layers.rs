@@ -0,0 +1,346 @@
+use crate::corridor_shard::CorridorSet;
+use crate::lyapunov_controller::{LyapunovController, MoveDecision, PlantModel};
+use crate::residual_groups::SubResidual;
+use std::time::Duration;
+
+/// Execution layers of grammar E.1, ordered fast to slow.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Timescale {
+    /// Combustion: air, fuel feed, dampers (10 ms – minutes).
+    Fast,
+    /// Scheduling: feed mix, load setpoint (minutes – hours).
+    Medium,
+    /// Maintenance/fouling: cleaning schedules, inspections (weeks – seasons).
+    Slow,
+}
+
+/// What a layer sees when it is asked for a proposal. The corridor set is
+/// the supervisor's own, so every layer reasons over the same r_x and w.
+pub struct LayerContext<'a> {
+    pub corridors: &'a CorridorSet,
+    pub v_t: f64,
+    pub now: Duration,
+}
+
+/// One control layer with its own tick period and internal state.
+pub trait ControlLayer<S, M> {
+    fn name(&self) -> &str;
+    fn timescale(&self) -> Timescale;
+    /// Interval between proposals; zero means every supervisor tick.
+    fn period(&self) -> Duration;
+    /// Proposed move for this tick, if any.
+    fn propose(&mut self, state: &S, ctx: &LayerContext) -> Option<M>;
+    /// Outcome of the layer's last proposal.
+    fn feedback(&mut self, _applied: bool, _decision: &MoveDecision) {}
+}
+
+/// A proposal and the admissibility verdict it received.
+#[derive(Clone, Debug)]
+pub struct LayerProposal {
+    pub layer: String,
+    pub timescale: Timescale,
+    pub decision: MoveDecision,
+}
+
+#[derive(Clone, Debug)]
+pub struct SupervisorStep<M> {
+    pub now: Duration,
+    /// Layer name and move that was applied, if any proposal was admissible.
+    pub applied: Option<(String, M)>,
+    pub proposals: Vec<LayerProposal>,
+}
+
+struct LayerSlot<S, M> {
+    layer: Box<dyn ControlLayer<S, M>>,
+    next_due: Duration,
+    /// Its last proposal was admissible but lost; it stays due.
+    deferred: bool,
+}
+
+/// Arbitrates between control layers. Every proposal is checked with the
+/// shared `LyapunovController`; among admissible ones the move with the
+/// lowest predicted V_{t+1} wins, ties going to the faster layer. If no
+/// proposal is admissible, no move is applied.
+///
+/// A layer whose admissible proposal lost keeps its due time and proposes
+/// again on the next tick, ahead of layers that were not deferred, so a fast
+/// layer acting every tick cannot starve a slower one.
+pub struct Supervisor<P: PlantModel> {
+    pub controller: LyapunovController<P>,
+    layers: Vec<LayerSlot<P::State, P::Move>>,
+    now: Duration,
+}
+
+impl<P: PlantModel> Supervisor<P>
+where
+    P::Move: Clone,
+{
+    pub fn new(controller: LyapunovController<P>) -> Self {
+        Supervisor {
+            controller,
+            layers: Vec::new(),
+            now: Duration::ZERO,
+        }
+    }
+
+    pub fn add_layer(&mut self, layer: Box<dyn ControlLayer<P::State, P::Move>>) {
+        self.layers.push(LayerSlot {
+            layer,
+            next_due: self.now,
+            deferred: false,
+        });
+    }
+
+    pub fn now(&self) -> Duration {
+        self.now
+    }
+
+    /// Advance the clock by `dt`, collect proposals from every due layer and
+    /// apply at most one admissible move. `v_t` and `groups_t` describe the
+    /// current measurements (`DualThresholdReport::residual` and `::groups`);
+    /// `groups_t` is the baseline for the controller's group limits and may be
+    /// empty when none are configured.
+    pub fn tick(
+        &mut self,
+        dt: Duration,
+        state: &P::State,
+        v_t: f64,
+        groups_t: &[SubResidual],
+    ) -> SupervisorStep<P::Move> {
+        self.now += dt;
+
+        let mut candidates: Vec<(usize, P::Move, MoveDecision)> = Vec::new();
+        for (idx, slot) in self.layers.iter_mut().enumerate() {
+            if slot.next_due > self.now {
+                continue;
+            }
+            let ctx = LayerContext {
+                corridors: &self.controller.corridors,
+                v_t,
+                now: self.now,
+            };
+            match slot.layer.propose(state, &ctx) {
+                Some(mv) => {
+                    let decision = self
+                        .controller
+                        .is_admissible_move_grouped(state, &mv, v_t, groups_t);
+                    candidates.push((idx, mv, decision));
+                }
+                None => {
+                    slot.next_due = self.now + slot.layer.period();
+                    slot.deferred = false;
+                }
+            }
+        }
+
+        let winner = candidates
+            .iter()
+            .enumerate()
+            .filter(|(_, (_, _, d))| d.admissible)
+            .min_by(|(_, (ia, _, da)), (_, (ib, _, db))| {
+                let (a, b) = (&self.layers[*ia], &self.layers[*ib]);
+                let va = da.predicted.as_ref().map_or(f64::INFINITY, |r| r.v);
+                let vb = db.predicted.as_ref().map_or(f64::INFINITY, |r| r.v);
+                b.deferred
+                    .cmp(&a.deferred)
+                    .then_with(|| va.total_cmp(&vb))
+                    .then_with(|| a.layer.timescale().cmp(&b.layer.timescale()))
+            })
+            .map(|(pos, _)| pos);
+
+        let mut applied = None;
+        let mut proposals = Vec::with_capacity(candidates.len());
+        for (pos, (idx, mv, decision)) in candidates.into_iter().enumerate() {
+            let slot = &mut self.layers[idx];
+            let is_winner = winner == Some(pos);
+            slot.deferred = decision.admissible && !is_winner;
+            if !slot.deferred {
+                slot.next_due = self.now + slot.layer.period();
+            }
+            let layer = &mut slot.layer;
+            layer.feedback(is_winner, &decision);
+            if is_winner {
+                applied = Some((layer.name().to_string(), mv));
+            }
+            proposals.push(LayerProposal {
+                layer: layer.name().to_string(),
+                timescale: layer.timescale(),
+                decision,
+            });
+        }
+
+        SupervisorStep {
+            now: self.now,
+            applied,
+            proposals,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::corridor_shard::parse_corridor_shard;
+    use crate::dual_threshold::{evaluate_thresholds, Measurement};
+    use crate::error::EcosafetyError;
+    use crate::lyapunov_controller::MoveReason;
+    use crate::residual_groups::{GroupLimit, ResidualGroup};
+    use std::collections::BTreeMap;
+
+    /// The move is the NOx setpoint itself.
+    struct Setpoint;
+
+    impl PlantModel for Setpoint {
+        type State = ();
+        type Move = f64;
+
+        fn predict(&self, _: &(), nox: &f64) -> Result<Vec<Measurement>, EcosafetyError> {
+            Ok(vec![Measurement {
+                param_name: "NOx".to_string(),
+                channel: 0,
+                value: *nox,
+                unit: "mg/Nm3".to_string(),
+            }])
+        }
+    }
+
+    /// The move is the (NOx, CO) setpoint pair.
+    struct Setpoints;
+
+    impl PlantModel for Setpoints {
+        type State = ();
+        type Move = (f64, f64);
+
+        fn predict(&self, _: &(), mv: &(f64, f64)) -> Result<Vec<Measurement>, EcosafetyError> {
+            Ok(vec![
+                Measurement {
+                    param_name: "NOx".to_string(),
+                    channel: 0,
+                    value: mv.0,
+                    unit: "mg/Nm3".to_string(),
+                },
+                Measurement {
+                    param_name: "CO".to_string(),
+                    channel: 1,
+                    value: mv.1,
+                    unit: "mg/Nm3".to_string(),
+                },
+            ])
+        }
+    }
+
+    /// Proposes the same move every period.
+    struct Fixed<M> {
+        name: &'static str,
+        timescale: Timescale,
+        period: Duration,
+        mv: M,
+    }
+
+    impl<M: Clone> ControlLayer<(), M> for Fixed<M> {
+        fn name(&self) -> &str {
+            self.name
+        }
+
+        fn timescale(&self) -> Timescale {
+            self.timescale
+        }
+
+        fn period(&self) -> Duration {
+            self.period
+        }
+
+        fn propose(&mut self, _: &(), _: &LayerContext) -> Option<M> {
+            Some(self.mv.clone())
+        }
+    }
+
+    #[test]
+    fn test_every_layer_eventually_acts() {
+        let corridors = parse_corridor_shard(
+            "node_id,parameter,unit,legal_limit,gold_limit,weight_w,channel\n\
+             PHX-TEST,NOx,mg/Nm3,200,150,1.0,0\n"
+                .as_bytes(),
+        )
+        .unwrap();
+        let mut sup = Supervisor::new(LyapunovController::new(corridors, Setpoint, 0.0));
+        // The fast layer always offers the lowest V and is due every tick.
+        for (name, timescale, period, nox) in [
+            ("combustion", Timescale::Fast, 0, 50.0),
+            ("scheduling", Timescale::Medium, 60, 80.0),
+            ("fouling", Timescale::Slow, 3600, 100.0),
+        ] {
+            sup.add_layer(Box::new(Fixed {
+                name,
+                timescale,
+                period: Duration::from_secs(period),
+                mv: nox,
+            }));
+        }
+
+        let mut acted: BTreeMap<String, Vec<u64>> = BTreeMap::new();
+        for _ in 0..7200 {
+            let step = sup.tick(Duration::from_secs(1), &(), 1.0, &[]);
+            if let Some((name, _)) = step.applied {
+                acted.entry(name).or_default().push(step.now.as_secs());
+            }
+        }
+        // Each layer acts within one tick per competing layer of falling due.
+        let n = 3;
+        for (name, period) in [("scheduling", 60), ("fouling", 3600)] {
+            let times = &acted[name];
+            assert!(times[0] <= 1 + n, "{}: {:?}", name, times);
+            for pair in times.windows(2) {
+                assert!(pair[1] - pair[0] <= period + n, "{}: {:?}", name, pair);
+            }
+        }
+        assert_eq!(acted["fouling"].len(), 2);
+        assert!(acted["combustion"].len() > 7000);
+    }
+
+    #[test]
+    fn test_group_limit_applies_under_the_supervisor() {
+        let corridors = parse_corridor_shard(
+            "node_id,parameter,unit,legal_limit,gold_limit,weight_w,ker_role,channel\n\
+             PHX-TEST,NOx,mg/Nm3,200,150,1.0,health,0\n\
+             PHX-TEST,CO,mg/Nm3,100,50,1.0,process,1\n"
+                .as_bytes(),
+        )
+        .unwrap();
+        let now = evaluate_thresholds(&corridors, &Setpoints.predict(&(), &(50.0, 80.0)).unwrap())
+            .unwrap();
+        let v_t = now.residual.as_ref().unwrap().v;
+        let health = ResidualGroup::role("health");
+
+        let controller = LyapunovController::new(corridors, Setpoints, 0.0)
+            .with_group_limit(GroupLimit::never_increase(health.clone()));
+        let mut sup = Supervisor::new(controller);
+        // Trading CO for NOx lowers V the most but raises the health residual.
+        for (name, timescale, mv) in [
+            ("trade", Timescale::Fast, (80.0, 20.0)),
+            ("clean", Timescale::Medium, (40.0, 70.0)),
+        ] {
+            sup.add_layer(Box::new(Fixed {
+                name,
+                timescale,
+                period: Duration::ZERO,
+                mv,
+            }));
+        }
+
+        let step = sup.tick(Duration::from_secs(1), &(), v_t, &now.groups);
+        assert_eq!(step.applied, Some(("clean".to_string(), (40.0, 70.0))));
+        assert!(matches!(
+            step.proposals[0].decision.reason,
+            MoveReason::GroupResidualIncrease { ref group, .. } if *group == health
+        ));
+
+        // Without a baseline nothing can be judged against the limit.
+        let step = sup.tick(Duration::from_secs(1), &(), v_t, &[]);
+        assert!(step.applied.is_none());
+        assert!(step.proposals.iter().all(|p| p.decision.reason
+            == MoveReason::MissingGroupBaseline {
+                group: health.clone()
+            }));
+    }
+}
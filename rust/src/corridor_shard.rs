@@ -0,0 +1,371 @@
+use crate::error::EcosafetyError;
+use crate::residual_form::CorridorForm;
+use crate::telemetry_shard::{ShardError, ShardRow, ShardTable};
+use crate::types::{Direction, Parameter, RiskCoordinateDef};
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+/// One validated corridor row: the K-layer parameter and its risk coordinate.
+#[derive(Clone, Debug)]
+pub struct CorridorEntry {
+    pub node_id: String,
+    pub parameter: Parameter,
+    pub risk: RiskCoordinateDef,
+    pub ker_role: Option<String>, // "health", "process", "safety" (particles schema)
+    pub ecoimpactscore: Option<f64>,
+}
+
+/// Corridor spec keyed by (parameter name, Lyapunov channel), plus the
+/// residual form V(r) the spec declares.
+#[derive(Clone, Debug, Default)]
+pub struct CorridorSet {
+    entries: BTreeMap<(String, u32), CorridorEntry>,
+    form: CorridorForm,
+}
+
+impl CorridorSet {
+    pub fn get(&self, param_name: &str, channel: u32) -> Option<&CorridorEntry> {
+        self.entries.get(&(param_name.to_string(), channel))
+    }
+
+    /// First entry for `param_name` on any channel.
+    pub fn by_param(&self, param_name: &str) -> Option<&CorridorEntry> {
+        self.entries
+            .values()
+            .find(|e| e.parameter.name == param_name)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &CorridorEntry> {
+        self.entries.values()
+    }
+
+    pub fn parameters(&self) -> Vec<Parameter> {
+        self.iter().map(|e| e.parameter.clone()).collect()
+    }
+
+    /// Risk coordinate definitions ordered by id (i.e. shard row order).
+    pub fn risk_defs(&self) -> Vec<RiskCoordinateDef> {
+        let mut defs: Vec<RiskCoordinateDef> = self.iter().map(|e| e.risk.clone()).collect();
+        defs.sort_by_key(|d| d.id);
+        defs
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn form(&self) -> &CorridorForm {
+        &self.form
+    }
+
+    /// Replace the declared residual form, e.g. to supply an explicit P for
+    /// the quadratic form. P must be sized to the corridor.
+    pub fn with_form(mut self, form: CorridorForm) -> Result<Self, EcosafetyError> {
+        if let CorridorForm::Quadratic(q) = &form {
+            if let Some(p) = q.matrix() {
+                if p.len() != self.len() {
+                    return Err(EcosafetyError::InvalidConfig {
+                        field: "P".to_string(),
+                        reason: format!(
+                            "P is {}x{} but the corridor has {} risk coordinates",
+                            p.len(),
+                            p.len(),
+                            self.len()
+                        ),
+                    });
+                }
+            }
+        }
+        self.form = form;
+        Ok(self)
+    }
+}
+
+/// A limit cell such as `150`, `>2.0` or `<=40`. The comparison marker, if
+/// any, fixes the parameter direction: `>` means the value must stay above
+/// the limit (MIN), `<` means below it (MAX).
+fn parse_limit(
+    row: &ShardRow,
+    col: usize,
+    field: &str,
+) -> Result<(f64, Option<Direction>), ShardError> {
+    let text = row.text(col, field)?;
+    let split = text
+        .find(|c: char| c != '<' && c != '>' && c != '=')
+        .unwrap_or(text.len());
+    let (marker, number) = text.split_at(split);
+    let direction = match marker {
+        "" => None,
+        "<" | "<=" => Some(Direction::Max),
+        ">" | ">=" => Some(Direction::Min),
+        _ => return Err(row.invalid(field, text, "number with optional > or < marker")),
+    };
+    match number.trim().parse::<f64>() {
+        Ok(x) if x.is_finite() => Ok((x, direction)),
+        _ => Err(row.invalid(field, text, "number with optional > or < marker")),
+    }
+}
+
+/// Parse a corridor spec shard. Both layouts in `qpudatashards/` are accepted:
+///
+/// - root: node_id,parameter,unit,legal_limit,gold_limit,r_min,r_max,weight_w,channel,...
+/// - particles: nodeid,parameter,unit,legal_limit,who_gold,weight_w,ker_role,lyap_channel,...
+///
+/// An optional `direction` column (MAX/MIN) states the direction explicitly;
+/// otherwise it follows the limit markers and defaults to MAX.
+///
+/// An optional `residual_form` column (LINEAR, QUADRATIC, MAX_NORM,
+/// LOG_BARRIER) declares how V is built from the risk coordinates. It must
+/// agree on every row; without it the form is LINEAR.
+///
+/// When r_min/r_max are absent the normalization bounds are derived from the
+/// legal limit: `[0, legal]` for MAX parameters, `[legal, 2·legal]` for MIN
+/// parameters, so r = 1 exactly at the legal limit in both cases. The gold
+/// limit must be at least as strict as the legal one for that direction.
+///
+/// Units are kept as written; an unknown unit only fails when a reading is
+/// converted against it (`compute_risk_coord_measured`).
+///
+/// A shard with a header but no rows is `NoRows`: no corridor, no deployment.
+pub fn parse_corridor_shard<R: BufRead>(reader: R) -> Result<CorridorSet, ShardError> {
+    let table = ShardTable::parse(reader)?;
+
+    let c_node = table
+        .find(&["node_id", "nodeid"])
+        .ok_or_else(|| ShardError::MissingColumn {
+            column: "node_id".to_string(),
+        })?;
+    let c_param = table.require("parameter")?;
+    let c_unit = table.require("unit")?;
+    let c_legal = table.require("legal_limit")?;
+    let c_gold =
+        table
+            .find(&["gold_limit", "who_gold"])
+            .ok_or_else(|| ShardError::MissingColumn {
+                column: "gold_limit".to_string(),
+            })?;
+    let c_w = table.require("weight_w")?;
+    let c_channel =
+        table
+            .find(&["channel", "lyap_channel"])
+            .ok_or_else(|| ShardError::MissingColumn {
+                column: "channel".to_string(),
+            })?;
+    let c_bounds = match (table.find(&["r_min"]), table.find(&["r_max"])) {
+        (Some(lo), Some(hi)) => Some((lo, hi)),
+        (None, None) => None,
+        (Some(_), None) => {
+            return Err(ShardError::MissingColumn {
+                column: "r_max".to_string(),
+            })
+        }
+        (None, Some(_)) => {
+            return Err(ShardError::MissingColumn {
+                column: "r_min".to_string(),
+            })
+        }
+    };
+    let c_direction = table.find(&["direction"]);
+    let c_role = table.find(&["ker_role"]);
+    let c_score = table.find(&["ecoimpactscore"]);
+    let c_form = table.find(&["residual_form"]);
+
+    let mut set = CorridorSet::default();
+    for (id, row) in table.rows.iter().enumerate() {
+        if let Some(c) = c_form {
+            let text = row.text(c, "residual_form")?;
+            let form = text.parse::<CorridorForm>().map_err(|_| {
+                row.invalid(
+                    "residual_form",
+                    text,
+                    "LINEAR, QUADRATIC, MAX_NORM or LOG_BARRIER",
+                )
+            })?;
+            if id == 0 {
+                set.form = form;
+            } else if form != set.form {
+                return Err(row.invalid("residual_form", text, "the same form on every row"));
+            }
+        }
+        let name = row.text(c_param, "parameter")?.to_string();
+        let (legal, legal_dir) = parse_limit(row, c_legal, "legal_limit")?;
+        let (gold, gold_dir) = parse_limit(row, c_gold, "gold_limit")?;
+        let stated_dir = match c_direction {
+            Some(c) => {
+                let text = row.text(c, "direction")?;
+                Some(
+                    text.parse::<Direction>()
+                        .map_err(|_| row.invalid("direction", text, "MAX or MIN"))?,
+                )
+            }
+            None => None,
+        };
+        let mut direction = None;
+        for (field, d) in [
+            ("direction", stated_dir),
+            ("legal_limit", legal_dir),
+            ("gold_limit", gold_dir),
+        ] {
+            match (direction, d) {
+                (Some(a), Some(b)) if a != b => {
+                    return Err(row.invalid(
+                        field,
+                        b.as_str(),
+                        "a direction consistent across columns",
+                    ));
+                }
+                (None, Some(b)) => direction = Some(b),
+                _ => {}
+            }
+        }
+        let direction = direction.unwrap_or(Direction::Max);
+        let gold_ok = match direction {
+            Direction::Max => gold <= legal,
+            Direction::Min => gold >= legal,
+        };
+        if !gold_ok {
+            return Err(row.invalid(
+                "gold_limit",
+                &gold.to_string(),
+                "a gold limit no looser than the legal limit",
+            ));
+        }
+
+        let (r_min, r_max) = match c_bounds {
+            Some((lo, hi)) => (row.real(lo, "r_min")?, row.real(hi, "r_max")?),
+            None => match direction {
+                Direction::Max => (0.0, legal),
+                Direction::Min => (legal, 2.0 * legal),
+            },
+        };
+        if r_max <= r_min {
+            return Err(row.invalid("r_max", &r_max.to_string(), "r_max greater than r_min"));
+        }
+
+        let weight_w = row.real(c_w, "weight_w")?;
+        if weight_w < 0.0 {
+            return Err(row.invalid("weight_w", &weight_w.to_string(), "non-negative weight"));
+        }
+        let channel = row.index(c_channel, "channel")?;
+        let unit = row.text(c_unit, "unit")?;
+
+        let entry = CorridorEntry {
+            node_id: row.text(c_node, "node_id")?.to_string(),
+            parameter: Parameter {
+                name: name.clone(),
+                unit: unit.to_string(),
+                domain_min: 0.0,
+                domain_max: f64::INFINITY,
+                legal_limit: Some(legal),
+                gold_limit: Some(gold),
+                direction,
+            },
+            risk: RiskCoordinateDef {
+                id: id as u32,
+                param_name: name.clone(),
+                r_min,
+                r_max,
+                weight_w,
+                channel,
+            },
+            ker_role: match c_role {
+                Some(c) => Some(row.text(c, "ker_role")?.to_string()),
+                None => None,
+            },
+            ecoimpactscore: match c_score {
+                Some(c) => Some(row.real(c, "ecoimpactscore")?),
+                None => None,
+            },
+        };
+
+        if set.entries.insert((name.clone(), channel), entry).is_some() {
+            return Err(row.invalid("parameter", &name, "unique parameter/channel pair"));
+        }
+    }
+
+    if set.is_empty() {
+        return Err(ShardError::NoRows);
+    }
+    Ok(set)
+}
+
+/// Load and validate a corridor spec shard from disk.
+pub fn load_corridor_shard<P: AsRef<Path>>(path: P) -> Result<CorridorSet, ShardError> {
+    let file = File::open(path)?;
+    parse_corridor_shard(BufReader::new(file))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ROOT: &str = include_str!("../../qpudatashards/CybocinderPhoenixCorridors2026v1.csv");
+    const PARTICLES: &str =
+        include_str!("../../qpudatashards/particles/CybocinderPhoenixCorridors2026v1.csv");
+
+    fn invalid_field(err: ShardError) -> String {
+        match err {
+            ShardError::InvalidField { field, .. } => field,
+            other => panic!("expected InvalidField, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_particles_layout_directions_and_derived_bounds() {
+        let set = parse_corridor_shard(PARTICLES.as_bytes()).unwrap();
+        assert_eq!(set.len(), 9);
+
+        let rt = set.get("ResidenceTime", 8).unwrap();
+        assert_eq!(rt.parameter.direction, Direction::Min);
+        assert_eq!((rt.risk.r_min, rt.risk.r_max), (2.0, 4.0));
+        assert_eq!(rt.ker_role.as_deref(), Some("safety"));
+
+        let nox = set.get("NOx", 0).unwrap();
+        assert_eq!(nox.parameter.direction, Direction::Max);
+        assert_eq!((nox.risk.r_min, nox.risk.r_max), (0.0, 150.0));
+        assert_eq!(nox.parameter.gold_limit, Some(40.0));
+    }
+
+    #[test]
+    fn test_root_layout_header_only_is_no_rows() {
+        assert!(matches!(
+            parse_corridor_shard(ROOT.as_bytes()),
+            Err(ShardError::NoRows)
+        ));
+        let one = format!("{}PHX-TEST,NOx,mg/Nm3,200,150,0,250,0.6,0,0.9,\n", ROOT);
+        let set = parse_corridor_shard(one.as_bytes()).unwrap();
+        let nox = set.get("NOx", 0).unwrap();
+        assert_eq!((nox.risk.r_min, nox.risk.r_max), (0.0, 250.0));
+        assert_eq!(nox.ecoimpactscore, Some(0.9));
+    }
+
+    #[test]
+    fn test_rejects_duplicates_and_loose_gold() {
+        let header = "node_id,parameter,unit,legal_limit,gold_limit,weight_w,channel\n";
+        let dup = format!(
+            "{}A,NOx,mg/Nm3,200,150,0.5,0\nB,NOx,mg/Nm3,200,100,0.5,0\n",
+            header
+        );
+        let err = parse_corridor_shard(dup.as_bytes()).unwrap_err();
+        assert_eq!(invalid_field(err), "parameter");
+        // Same parameter on another channel is a distinct coordinate.
+        let split = format!(
+            "{}A,NOx,mg/Nm3,200,150,0.5,0\nB,NOx,mg/Nm3,200,100,0.5,1\n",
+            header
+        );
+        assert_eq!(parse_corridor_shard(split.as_bytes()).unwrap().len(), 2);
+
+        let loose_max = format!("{}A,NOx,mg/Nm3,200,250,0.5,0\n", header);
+        let err = parse_corridor_shard(loose_max.as_bytes()).unwrap_err();
+        assert_eq!(invalid_field(err), "gold_limit");
+        let loose_min = format!("{}A,O2,vol%,>6,>4,0.5,0\n", header);
+        let err = parse_corridor_shard(loose_min.as_bytes()).unwrap_err();
+        assert_eq!(invalid_field(err), "gold_limit");
+    }
+}
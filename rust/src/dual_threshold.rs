@@ -0,0 +1,263 @@
+use crate::corridor_shard::CorridorSet;
+use crate::error::EcosafetyError;
+use crate::gates::{compute_gates, GateResult, ResidualFlags};
+use crate::lyapunov::{compute_risk_coord_measured, to_param_unit, ResidualState};
+use crate::residual_form::{compute_residual_with, ResidualForm};
+use crate::residual_groups::{group_residual, sub_residuals, ResidualGroup, SubResidual};
+use crate::telemetry_shard::TelemetryRecord;
+use crate::types::Direction;
+
+/// One measured value for a corridor parameter on a Lyapunov channel.
+#[derive(Clone, Debug)]
+pub struct Measurement {
+    pub param_name: String,
+    pub channel: u32,
+    pub value: f64,
+    pub unit: String,
+}
+
+impl From<&TelemetryRecord> for Measurement {
+    fn from(rec: &TelemetryRecord) -> Self {
+        Measurement {
+            param_name: rec.param_name.clone(),
+            channel: rec.channel,
+            value: rec.value,
+            unit: rec.unit.clone(),
+        }
+    }
+}
+
+/// Legal/gold status of one corridor parameter. `value` is expressed in the
+/// parameter's own unit; it is `None` when no measurement was supplied, in
+/// which case every check fails.
+#[derive(Clone, Debug)]
+pub struct ThresholdStatus {
+    pub param_name: String,
+    pub channel: u32,
+    pub direction: Direction,
+    pub value: Option<f64>,
+    pub legal_limit: Option<f64>,
+    pub gold_limit: Option<f64>,
+    pub r: Option<f64>,
+    pub corridor_ok: bool,
+    pub legal_ok: bool,
+    pub gold_ok: bool,
+}
+
+#[derive(Clone, Debug)]
+pub struct DualThresholdReport {
+    pub statuses: Vec<ThresholdStatus>,
+    pub flags: ResidualFlags,
+    /// Residual over the measured coordinates, in the corridor's declared form;
+    /// `None` if nothing was measured, or if a coordinate is missing and the
+    /// form couples coordinates (explicit-P quadratic).
+    pub residual: Option<ResidualState>,
+    /// Sub-residuals per Lyapunov channel and per KER role.
+    pub groups: Vec<SubResidual>,
+}
+
+impl DualThresholdReport {
+    pub fn group(&self, group: &ResidualGroup) -> Option<f64> {
+        group_residual(&self.groups, group)
+    }
+
+    /// Drive `compute_gates` from the measured flags and residual.
+    pub fn gates(&self, v_prev: f64, eps: f64, lca_ok: bool, pilot_gates_ok: bool) -> GateResult {
+        match &self.residual {
+            Some(res) => compute_gates(&self.flags, v_prev, res.v, eps, lca_ok, pilot_gates_ok),
+            // No corridor data: treat the residual as unbounded.
+            None => compute_gates(
+                &self.flags,
+                v_prev,
+                f64::INFINITY,
+                eps,
+                lca_ok,
+                pilot_gates_ok,
+            ),
+        }
+    }
+}
+
+/// Whether `x` is strictly on the safe side of `limit`. A reading exactly at
+/// a limit fails it, matching r = 1 (not `corridor_ok`) at the legal limit.
+pub(crate) fn within(direction: Direction, x: f64, limit: Option<f64>) -> bool {
+    match (direction, limit) {
+        (_, None) => true,
+        (Direction::Max, Some(l)) => x < l,
+        (Direction::Min, Some(l)) => x > l,
+    }
+}
+
+/// Compare measurements against the legal and gold limits of every corridor
+/// parameter, respecting MAX/MIN direction, and aggregate into `ResidualFlags`.
+/// A reading exactly at a limit fails it, as r = 1 there fails the corridor.
+/// A corridor parameter without a measurement fails all three flags;
+/// measurements for parameters outside the corridor set are ignored.
+pub fn evaluate_thresholds(
+    corridors: &CorridorSet,
+    measurements: &[Measurement],
+) -> Result<DualThresholdReport, EcosafetyError> {
+    if corridors.is_empty() {
+        return Err(EcosafetyError::NoCorridor);
+    }
+
+    let mut statuses = Vec::with_capacity(corridors.len());
+    let mut coords = Vec::with_capacity(corridors.len());
+    for entry in corridors.iter() {
+        let p = &entry.parameter;
+        let mut matching = measurements
+            .iter()
+            .filter(|m| m.param_name == p.name && m.channel == entry.risk.channel);
+        let m = matching.next();
+        if matching.next().is_some() {
+            return Err(EcosafetyError::InvalidConfig {
+                field: p.name.clone(),
+                reason: format!("duplicate measurement on channel {}", entry.risk.channel),
+            });
+        }
+
+        let status = match m {
+            None => ThresholdStatus {
+                param_name: p.name.clone(),
+                channel: entry.risk.channel,
+                direction: p.direction,
+                value: None,
+                legal_limit: p.legal_limit,
+                gold_limit: p.gold_limit,
+                r: None,
+                corridor_ok: false,
+                legal_ok: false,
+                gold_ok: false,
+            },
+            Some(m) => {
+                let x = to_param_unit(p, m.value, &m.unit)?;
+                let rc = compute_risk_coord_measured(p, &entry.risk, m.value, &m.unit)?;
+                let status = ThresholdStatus {
+                    param_name: p.name.clone(),
+                    channel: entry.risk.channel,
+                    direction: p.direction,
+                    value: Some(x),
+                    legal_limit: p.legal_limit,
+                    gold_limit: p.gold_limit,
+                    r: Some(rc.r),
+                    corridor_ok: rc.r < 1.0,
+                    legal_ok: within(p.direction, x, p.legal_limit),
+                    gold_ok: within(p.direction, x, p.gold_limit),
+                };
+                coords.push(rc);
+                status
+            }
+        };
+        statuses.push(status);
+    }
+
+    let flags = ResidualFlags {
+        corridor_ok: statuses.iter().all(|s| s.corridor_ok),
+        legal_ok: statuses.iter().all(|s| s.legal_ok),
+        gold_ok: statuses.iter().all(|s| s.gold_ok),
+    };
+    let form = corridors.form();
+    let residual = if coords.is_empty() || (coords.len() < statuses.len() && !form.separable()) {
+        None
+    } else {
+        Some(compute_residual_with(form, &coords)?)
+    };
+    let r: Vec<Option<f64>> = statuses.iter().map(|s| s.r).collect();
+    let groups = sub_residuals(corridors, &r)?;
+
+    Ok(DualThresholdReport {
+        statuses,
+        flags,
+        residual,
+        groups,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::corridor_shard::parse_corridor_shard;
+
+    const CORRIDORS: &str = "\
+node_id,parameter,unit,legal_limit,gold_limit,weight_w,channel
+PHX-TEST,NOx,mg/Nm3,200,150,0.6,0
+PHX-TEST,ResidenceTime,s,2.0,>2.5,0.4,1
+";
+
+    fn m(param_name: &str, channel: u32, value: f64, unit: &str) -> Measurement {
+        Measurement {
+            param_name: param_name.to_string(),
+            channel,
+            value,
+            unit: unit.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_reading_at_the_legal_limit_fails_legal_and_corridor() {
+        let corridors = parse_corridor_shard(CORRIDORS.as_bytes()).unwrap();
+        let at = evaluate_thresholds(
+            &corridors,
+            &[
+                m("NOx", 0, 200.0, "mg/Nm3"),
+                m("ResidenceTime", 1, 2.0, "s"),
+            ],
+        )
+        .unwrap();
+        for s in &at.statuses {
+            assert_eq!(s.r, Some(1.0), "{}", s.param_name);
+            assert!(
+                !s.corridor_ok && !s.legal_ok && !s.gold_ok,
+                "{}",
+                s.param_name
+            );
+        }
+
+        let inside = evaluate_thresholds(
+            &corridors,
+            &[
+                m("NOx", 0, 120.0, "mg/Nm3"),
+                m("ResidenceTime", 1, 3.0, "s"),
+            ],
+        )
+        .unwrap();
+        assert!(inside.flags.corridor_ok && inside.flags.legal_ok && inside.flags.gold_ok);
+        let v = inside.residual.unwrap().v;
+        // NOx r = 0.6, ResidenceTime r = (4 − 3) / 2 = 0.5.
+        assert!((v - (0.6 * 0.6 + 0.4 * 0.5)).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_readings_are_converted_into_the_corridor_unit() {
+        let corridors = parse_corridor_shard(CORRIDORS.as_bytes()).unwrap();
+        let report = evaluate_thresholds(
+            &corridors,
+            &[
+                m("NOx", 0, 120_000.0, "ug/Nm3"),
+                m("ResidenceTime", 1, 0.05, "min"),
+            ],
+        )
+        .unwrap();
+        assert!((report.statuses[0].value.unwrap() - 120.0).abs() < 1e-9);
+        assert!((report.statuses[1].value.unwrap() - 3.0).abs() < 1e-12);
+
+        let err = evaluate_thresholds(&corridors, &[m("NOx", 0, 120.0, "ug/m3")]).unwrap_err();
+        assert!(matches!(err, EcosafetyError::UnitMismatch { .. }));
+    }
+
+    #[test]
+    fn test_missing_and_duplicate_measurements() {
+        let corridors = parse_corridor_shard(CORRIDORS.as_bytes()).unwrap();
+        let report = evaluate_thresholds(&corridors, &[m("NOx", 0, 120.0, "mg/Nm3")]).unwrap();
+        assert!(!report.flags.corridor_ok && !report.flags.legal_ok);
+        assert_eq!(report.statuses[1].value, None);
+        assert!((report.residual.unwrap().v - 0.36).abs() < 1e-12);
+
+        let twice = [m("NOx", 0, 120.0, "mg/Nm3"), m("NOx", 0, 121.0, "mg/Nm3")];
+        assert!(matches!(
+            evaluate_thresholds(&corridors, &twice),
+            Err(EcosafetyError::InvalidConfig { .. })
+        ));
+        assert!(evaluate_thresholds(&CorridorSet::default(), &[]).is_err());
+    }
+}
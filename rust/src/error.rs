@@ -0,0 +1,186 @@
+use crate::contracts::ContractError;
+use crate::types::{FunctionalUnit, ScenarioMode};
+use std::error::Error;
+use std::fmt;
+
+/// Errors raised by the gate math. Each variant names the scenario, parameter
+/// or risk coordinate that caused it, so a malformed shard row can be traced
+/// without taking the gate service down.
+#[derive(Clone, Debug, PartialEq)]
+pub enum EcosafetyError {
+    RegionMismatch {
+        base_scenario_id: String,
+        cybo_scenario_id: String,
+        base_region: String,
+        cybo_region: String,
+    },
+    FunctionalUnitMismatch {
+        base_scenario_id: String,
+        cybo_scenario_id: String,
+        base_unit: FunctionalUnit,
+        cybo_unit: FunctionalUnit,
+    },
+    UnexpectedMode {
+        scenario_id: String,
+        expected: ScenarioMode,
+        found: ScenarioMode,
+    },
+    InvalidLcaParameter {
+        scenario_id: String,
+        field: &'static str,
+        value: f64,
+    },
+    MissingLcaParameter {
+        scenario_id: String,
+        field: &'static str,
+    },
+    AmbiguousScenario {
+        region_id: String,
+        functional_unit: FunctionalUnit,
+        mode: ScenarioMode,
+        scenario_ids: Vec<String>,
+    },
+    UnitMismatch {
+        param_name: String,
+        expected: String,
+        found: String,
+    },
+    InvalidNormalization {
+        coord_id: u32,
+        param_name: String,
+        r_min: f64,
+        r_max: f64,
+    },
+    NegativeWeight {
+        coord_id: u32,
+        param_name: String,
+        weight: f64,
+    },
+    NonFiniteWeight {
+        coord_id: u32,
+        param_name: String,
+        weight: f64,
+    },
+    InvalidConfig {
+        field: String,
+        reason: String,
+    },
+    /// The generated `spec/*.aln` contract code could not be evaluated.
+    Contract {
+        contract: &'static str,
+        error: ContractError,
+    },
+    /// Hard rule: no corridor, no deployment.
+    NoCorridor,
+}
+
+impl fmt::Display for EcosafetyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EcosafetyError::RegionMismatch {
+                base_scenario_id,
+                cybo_scenario_id,
+                base_region,
+                cybo_region,
+            } => write!(
+                f,
+                "scenarios {} and {} are for different regions ({} vs {})",
+                base_scenario_id, cybo_scenario_id, base_region, cybo_region
+            ),
+            EcosafetyError::FunctionalUnitMismatch {
+                base_scenario_id,
+                cybo_scenario_id,
+                base_unit,
+                cybo_unit,
+            } => write!(
+                f,
+                "scenarios {} and {} use different functional units ({} vs {})",
+                base_scenario_id, cybo_scenario_id, base_unit, cybo_unit
+            ),
+            EcosafetyError::UnexpectedMode {
+                scenario_id,
+                expected,
+                found,
+            } => write!(
+                f,
+                "scenario {} has mode {} (expected {})",
+                scenario_id, found, expected
+            ),
+            EcosafetyError::InvalidLcaParameter {
+                scenario_id,
+                field,
+                value,
+            } => write!(
+                f,
+                "scenario {}: {} = {} is outside its valid range",
+                scenario_id, field, value
+            ),
+            EcosafetyError::MissingLcaParameter { scenario_id, field } => write!(
+                f,
+                "scenario {}: {} is required but not given",
+                scenario_id, field
+            ),
+            EcosafetyError::AmbiguousScenario {
+                region_id,
+                functional_unit,
+                mode,
+                scenario_ids,
+            } => write!(
+                f,
+                "region {} has several {} scenarios for {}: {}",
+                region_id,
+                mode,
+                functional_unit,
+                scenario_ids.join(", ")
+            ),
+            EcosafetyError::UnitMismatch {
+                param_name,
+                expected,
+                found,
+            } => write!(
+                f,
+                "{}: measurement in {} cannot be used against a corridor in {}",
+                param_name, found, expected
+            ),
+            EcosafetyError::InvalidNormalization {
+                coord_id,
+                param_name,
+                r_min,
+                r_max,
+            } => write!(
+                f,
+                "risk coordinate {} ({}): invalid r_min/r_max {} / {}",
+                coord_id, param_name, r_min, r_max
+            ),
+            EcosafetyError::NegativeWeight {
+                coord_id,
+                param_name,
+                weight,
+            } => write!(
+                f,
+                "risk coordinate {} ({}): negative weight {}",
+                coord_id, param_name, weight
+            ),
+            EcosafetyError::NonFiniteWeight {
+                coord_id,
+                param_name,
+                weight,
+            } => write!(
+                f,
+                "risk coordinate {} ({}): weight {} is not finite",
+                coord_id, param_name, weight
+            ),
+            EcosafetyError::InvalidConfig { field, reason } => {
+                write!(f, "invalid configuration for {}: {}", field, reason)
+            }
+            EcosafetyError::Contract { contract, error } => {
+                write!(f, "contract {}: {}", contract, error)
+            }
+            EcosafetyError::NoCorridor => {
+                write!(f, "no risk coordinates (no corridor -> no deployment)")
+            }
+        }
+    }
+}
+
+impl Error for EcosafetyError {}
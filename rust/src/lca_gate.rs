@@ -1,12 +1,497 @@
-use crate::types::LcaScenario;
+use crate::contracts::lca_deployment_gate as contract;
+use crate::error::EcosafetyError;
+use crate::types::{FunctionalUnit, LcaScenario, ScenarioMode};
+use std::collections::{BTreeMap, BTreeSet};
 
+/// Both scenarios must describe the same region and functional unit, with
+/// `base` the STATUS_QUO and `cybo` the CYBOCINDER side.
+fn check_pair(base: &LcaScenario, cybo: &LcaScenario) -> Result<(), EcosafetyError> {
+    if base.region_id != cybo.region_id {
+        return Err(EcosafetyError::RegionMismatch {
+            base_scenario_id: base.scenario_id.clone(),
+            cybo_scenario_id: cybo.scenario_id.clone(),
+            base_region: base.region_id.clone(),
+            cybo_region: cybo.region_id.clone(),
+        });
+    }
+    if base.functional_unit != cybo.functional_unit {
+        return Err(EcosafetyError::FunctionalUnitMismatch {
+            base_scenario_id: base.scenario_id.clone(),
+            cybo_scenario_id: cybo.scenario_id.clone(),
+            base_unit: base.functional_unit,
+            cybo_unit: cybo.functional_unit,
+        });
+    }
+    if base.mode != ScenarioMode::StatusQuo {
+        return Err(EcosafetyError::UnexpectedMode {
+            scenario_id: base.scenario_id.clone(),
+            expected: ScenarioMode::StatusQuo,
+            found: base.mode,
+        });
+    }
+    if cybo.mode != ScenarioMode::Cybocinder {
+        return Err(EcosafetyError::UnexpectedMode {
+            scenario_id: cybo.scenario_id.clone(),
+            expected: ScenarioMode::Cybocinder,
+            found: cybo.mode,
+        });
+    }
+    Ok(())
+}
+
+/// INVARIANT PrimaryLca of `spec/lca-gates.cybo.aln` on a checked pair.
 pub fn lca_ok(
     base: &LcaScenario,
     cybo: &LcaScenario,
-) -> bool {
-    assert_eq!(base.region_id, cybo.region_id);
-    assert_eq!(base.functional_unit, cybo.functional_unit);
-    assert_eq!(base.mode, "STATUS_QUO");
-    assert_eq!(cybo.mode, "CYBOCINDER");
-    cybo.gwp_kg_co2eq < base.gwp_kg_co2eq
+) -> Result<bool, EcosafetyError> {
+    check_pair(base, cybo)?;
+    let inp = contract::Inputs {
+        gwp_base: base.gwp_kg_co2eq,
+        gwp_cybo: cybo.gwp_kg_co2eq,
+    };
+    let k = contract::Constants {};
+    contract::check_primary_lca(&k, &inp, &contract::Outputs {}).map_err(|error| {
+        EcosafetyError::Contract {
+            contract: contract::CONTRACT,
+            error,
+        }
+    })
+}
+
+/// Functional units for which a region must show a strict GWP improvement.
+pub const REQUIRED_FUNCTIONAL_UNITS: [FunctionalUnit; 3] = FunctionalUnit::ALL;
+
+/// Verdict for one functional unit of a region.
+#[derive(Clone, Debug)]
+pub struct FuVerdict {
+    pub functional_unit: FunctionalUnit,
+    pub base_scenario_id: Option<String>,
+    pub cybo_scenario_id: Option<String>,
+    pub gwp_base: Option<f64>,
+    pub gwp_cybo: Option<f64>,
+    pub ok: bool,
+    pub reason: String,
+}
+
+/// Per-FU verdict table for a region; deployment passes only if every row does.
+#[derive(Clone, Debug)]
+pub struct RegionLcaVerdict {
+    pub region_id: String,
+    pub per_fu: Vec<FuVerdict>,
+    pub deployment_ok: bool,
+}
+
+impl RegionLcaVerdict {
+    /// Functional units that blocked deployment.
+    pub fn blocking(&self) -> Vec<&FuVerdict> {
+        self.per_fu.iter().filter(|v| !v.ok).collect()
+    }
+}
+
+fn single<'a>(
+    region_id: &str,
+    functional_unit: FunctionalUnit,
+    mode: ScenarioMode,
+    scenarios: &[&'a LcaScenario],
+) -> Result<Option<&'a LcaScenario>, EcosafetyError> {
+    let found: Vec<&LcaScenario> = scenarios
+        .iter()
+        .copied()
+        .filter(|s| s.functional_unit == functional_unit && s.mode == mode)
+        .collect();
+    if found.len() > 1 {
+        return Err(EcosafetyError::AmbiguousScenario {
+            region_id: region_id.to_string(),
+            functional_unit,
+            mode,
+            scenario_ids: found.iter().map(|s| s.scenario_id.clone()).collect(),
+        });
+    }
+    Ok(found.first().copied())
+}
+
+/// Deployment gate over every scenario of a region: a STATUS_QUO/CYBOCINDER
+/// pair must exist for each of `REQUIRED_FUNCTIONAL_UNITS` and `lca_ok` must
+/// hold for all of them. Scenarios for other regions are ignored.
+pub fn region_lca_gate(
+    region_id: &str,
+    scenarios: &[LcaScenario],
+) -> Result<RegionLcaVerdict, EcosafetyError> {
+    let in_region: Vec<&LcaScenario> = scenarios
+        .iter()
+        .filter(|s| s.region_id == region_id)
+        .collect();
+
+    let mut per_fu = Vec::with_capacity(REQUIRED_FUNCTIONAL_UNITS.len());
+    for fu in REQUIRED_FUNCTIONAL_UNITS {
+        let base = single(region_id, fu, ScenarioMode::StatusQuo, &in_region)?;
+        let cybo = single(region_id, fu, ScenarioMode::Cybocinder, &in_region)?;
+        let (ok, reason) = match (base, cybo) {
+            (Some(b), Some(c)) => {
+                if lca_ok(b, c)? {
+                    (true, "GWP_cybo < GWP_base".to_string())
+                } else {
+                    (
+                        false,
+                        format!(
+                            "GWP_cybo {} is not below GWP_base {}",
+                            c.gwp_kg_co2eq, b.gwp_kg_co2eq
+                        ),
+                    )
+                }
+            }
+            (None, Some(_)) => (false, "no STATUS_QUO scenario".to_string()),
+            (Some(_), None) => (false, "no CYBOCINDER scenario".to_string()),
+            (None, None) => (false, "no scenarios".to_string()),
+        };
+        per_fu.push(FuVerdict {
+            functional_unit: fu,
+            base_scenario_id: base.map(|s| s.scenario_id.clone()),
+            cybo_scenario_id: cybo.map(|s| s.scenario_id.clone()),
+            gwp_base: base.map(|s| s.gwp_kg_co2eq),
+            gwp_cybo: cybo.map(|s| s.gwp_kg_co2eq),
+            ok,
+            reason,
+        });
+    }
+
+    let deployment_ok = per_fu.iter().all(|v| v.ok);
+    Ok(RegionLcaVerdict {
+        region_id: region_id.to_string(),
+        per_fu,
+        deployment_ok,
+    })
+}
+
+/// How a co-impact category (acidification, PM, ecotoxicity, ...) enters the gate.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ImpactRequirement {
+    /// I_cybo < I_base is required.
+    MustImprove,
+    /// I_cybo ≤ I_base + |I_base| · tolerance; tolerance is a relative
+    /// fraction of the baseline's magnitude, so a negative (net-avoided)
+    /// baseline still allows a rise towards zero, never a further drop.
+    MayDegrade { tolerance: f64 },
+    /// Reported but never blocks deployment.
+    Informational,
+}
+
+/// Co-impact policy keyed by category name. Categories present in a scenario
+/// but absent from the policy are reported as informational.
+#[derive(Clone, Debug, Default)]
+pub struct CoImpactPolicy {
+    pub categories: BTreeMap<String, ImpactRequirement>,
+}
+
+impl CoImpactPolicy {
+    pub fn must_improve(mut self, category: &str) -> Self {
+        self.categories
+            .insert(category.to_string(), ImpactRequirement::MustImprove);
+        self
+    }
+
+    pub fn may_degrade(mut self, category: &str, tolerance: f64) -> Self {
+        self.categories.insert(
+            category.to_string(),
+            ImpactRequirement::MayDegrade { tolerance },
+        );
+        self
+    }
+
+    pub fn informational(mut self, category: &str) -> Self {
+        self.categories
+            .insert(category.to_string(), ImpactRequirement::Informational);
+        self
+    }
+}
+
+/// Verdict for one co-impact category.
+#[derive(Clone, Debug)]
+pub struct CategoryVerdict {
+    pub category: String,
+    pub requirement: ImpactRequirement,
+    pub base: Option<f64>,
+    pub cybo: Option<f64>,
+    pub ok: bool,
+    pub reason: String,
+}
+
+/// Primary GWP verdict plus the per-category co-impact table.
+#[derive(Clone, Debug)]
+pub struct CoImpactVerdict {
+    pub gwp_ok: bool,
+    pub categories: Vec<CategoryVerdict>,
+    pub ok: bool,
+}
+
+impl CoImpactVerdict {
+    /// Categories that blocked deployment.
+    pub fn blocking(&self) -> Vec<&CategoryVerdict> {
+        self.categories.iter().filter(|v| !v.ok).collect()
+    }
+}
+
+/// Primary LCA invariant plus co-impact policy. A required category that is
+/// missing from either scenario fails: an unproven improvement does not pass.
+/// A negative or non-finite MayDegrade tolerance is a configuration error.
+pub fn lca_ok_with_policy(
+    base: &LcaScenario,
+    cybo: &LcaScenario,
+    policy: &CoImpactPolicy,
+) -> Result<CoImpactVerdict, EcosafetyError> {
+    for (name, requirement) in &policy.categories {
+        if let ImpactRequirement::MayDegrade { tolerance } = requirement {
+            if !tolerance.is_finite() || *tolerance < 0.0 {
+                return Err(EcosafetyError::InvalidConfig {
+                    field: name.clone(),
+                    reason: format!("MayDegrade tolerance {} must be >= 0", tolerance),
+                });
+            }
+        }
+    }
+    let gwp_ok = lca_ok(base, cybo)?;
+
+    let names: BTreeSet<&String> = policy
+        .categories
+        .keys()
+        .chain(base.other_impacts.keys())
+        .chain(cybo.other_impacts.keys())
+        .collect();
+
+    let mut categories = Vec::with_capacity(names.len());
+    for name in names {
+        let requirement = policy
+            .categories
+            .get(name)
+            .cloned()
+            .unwrap_or(ImpactRequirement::Informational);
+        let b = base.other_impacts.get(name).copied();
+        let c = cybo.other_impacts.get(name).copied();
+        let (ok, reason) = match (&requirement, b, c) {
+            (ImpactRequirement::Informational, _, _) => (true, "informational".to_string()),
+            (_, None, _) => (false, format!("{} missing from {}", name, base.scenario_id)),
+            (_, _, None) => (false, format!("{} missing from {}", name, cybo.scenario_id)),
+            (ImpactRequirement::MustImprove, Some(b), Some(c)) => {
+                if c < b {
+                    (true, "improved".to_string())
+                } else {
+                    (false, format!("{} is not below baseline {}", c, b))
+                }
+            }
+            (ImpactRequirement::MayDegrade { tolerance }, Some(b), Some(c)) => {
+                let limit = b + b.abs() * tolerance;
+                if c <= limit {
+                    (true, "within tolerance".to_string())
+                } else {
+                    (false, format!("{} exceeds tolerated {}", c, limit))
+                }
+            }
+        };
+        categories.push(CategoryVerdict {
+            category: name.clone(),
+            requirement,
+            base: b,
+            cybo: c,
+            ok,
+            reason,
+        });
+    }
+
+    let ok = gwp_ok && categories.iter().all(|v| v.ok);
+    Ok(CoImpactVerdict {
+        gwp_ok,
+        categories,
+        ok,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scenario(id: &str, fu: FunctionalUnit, mode: ScenarioMode, gwp: f64) -> LcaScenario {
+        LcaScenario {
+            scenario_id: id.to_string(),
+            region_id: "PHX".to_string(),
+            functional_unit: fu,
+            mode,
+            gwp_kg_co2eq: gwp,
+            grid_gco2_per_kwh: 420.0,
+            landfill_ref_gwp_kgco2_per_ton: None,
+            avoided_virgin_metal_kgco2eq_per_kg: None,
+            energy_recovery_efficiency: None,
+            recycling_rate: None,
+            other_impacts: BTreeMap::new(),
+        }
+    }
+
+    #[test]
+    fn test_malformed_pairs_are_errors_not_panics() {
+        let base = scenario("B", FunctionalUnit::MswTon, ScenarioMode::StatusQuo, 480.0);
+        let cybo = scenario("C", FunctionalUnit::MswTon, ScenarioMode::Cybocinder, 320.0);
+        assert!(lca_ok(&base, &cybo).unwrap());
+
+        let mut elsewhere = cybo.clone();
+        elsewhere.region_id = "TUS".to_string();
+        assert!(matches!(
+            lca_ok(&base, &elsewhere),
+            Err(EcosafetyError::RegionMismatch { .. })
+        ));
+        let mut energy = cybo.clone();
+        energy.functional_unit = FunctionalUnit::EnergyMwh;
+        assert!(matches!(
+            lca_ok(&base, &energy),
+            Err(EcosafetyError::FunctionalUnitMismatch { .. })
+        ));
+        assert_eq!(
+            lca_ok(&cybo, &base).unwrap_err(),
+            EcosafetyError::UnexpectedMode {
+                scenario_id: "C".to_string(),
+                expected: ScenarioMode::StatusQuo,
+                found: ScenarioMode::Cybocinder,
+            }
+        );
+    }
+
+    fn region(extra: &[LcaScenario]) -> Vec<LcaScenario> {
+        let mut all = Vec::new();
+        for (i, fu) in FunctionalUnit::ALL.into_iter().enumerate() {
+            all.push(scenario(
+                &format!("B{}", i),
+                fu,
+                ScenarioMode::StatusQuo,
+                10.0,
+            ));
+            all.push(scenario(
+                &format!("C{}", i),
+                fu,
+                ScenarioMode::Cybocinder,
+                5.0,
+            ));
+        }
+        all.extend_from_slice(extra);
+        all
+    }
+
+    #[test]
+    fn test_region_gate_needs_one_pair_per_functional_unit() {
+        let verdict = region_lca_gate("PHX", &region(&[])).unwrap();
+        assert!(verdict.deployment_ok);
+        assert_eq!(verdict.per_fu.len(), 3);
+
+        // Other regions are ignored, so they cannot make the gate ambiguous.
+        let mut tus = scenario(
+            "T",
+            FunctionalUnit::EnergyMwh,
+            ScenarioMode::Cybocinder,
+            1.0,
+        );
+        tus.region_id = "TUS".to_string();
+        assert!(
+            region_lca_gate("PHX", &region(&[tus]))
+                .unwrap()
+                .deployment_ok
+        );
+
+        let dup = scenario(
+            "C1b",
+            FunctionalUnit::EnergyMwh,
+            ScenarioMode::Cybocinder,
+            4.0,
+        );
+        assert_eq!(
+            region_lca_gate("PHX", &region(&[dup])).unwrap_err(),
+            EcosafetyError::AmbiguousScenario {
+                region_id: "PHX".to_string(),
+                functional_unit: FunctionalUnit::EnergyMwh,
+                mode: ScenarioMode::Cybocinder,
+                scenario_ids: vec!["C1".to_string(), "C1b".to_string()],
+            }
+        );
+
+        let mut partial = region(&[]);
+        partial.retain(|s| s.scenario_id != "B2");
+        let verdict = region_lca_gate("PHX", &partial).unwrap();
+        assert!(!verdict.deployment_ok);
+        let blocking = verdict.blocking();
+        assert_eq!(blocking.len(), 1);
+        assert_eq!(blocking[0].functional_unit, FunctionalUnit::ResourceKg);
+    }
+
+    #[test]
+    fn test_may_degrade_tolerance_edges() {
+        let mut base = scenario("B", FunctionalUnit::MswTon, ScenarioMode::StatusQuo, 480.0);
+        let mut cybo = scenario("C", FunctionalUnit::MswTon, ScenarioMode::Cybocinder, 320.0);
+        base.other_impacts.insert("PM".to_string(), 2.0);
+        base.other_impacts.insert("ecotoxicity".to_string(), -4.0);
+        let policy = CoImpactPolicy::default()
+            .may_degrade("PM", 0.25)
+            .may_degrade("ecotoxicity", 0.5);
+
+        let verdict = |cybo: &LcaScenario| lca_ok_with_policy(&base, cybo, &policy).unwrap();
+        // Exactly at b + |b|·tol passes; the tolerance scales with |b|.
+        cybo.other_impacts.insert("PM".to_string(), 2.5);
+        cybo.other_impacts.insert("ecotoxicity".to_string(), -2.0);
+        assert!(verdict(&cybo).ok);
+        cybo.other_impacts.insert("PM".to_string(), 2.5 + 1e-9);
+        let v = verdict(&cybo);
+        assert!(!v.ok);
+        assert_eq!(v.blocking()[0].category, "PM");
+        // A category the cybocinder scenario does not report is not tolerated.
+        cybo.other_impacts.remove("PM");
+        assert!(!verdict(&cybo).ok);
+
+        let zero = CoImpactPolicy::default().may_degrade("PM", 0.0);
+        cybo.other_impacts.insert("PM".to_string(), 2.0);
+        assert!(lca_ok_with_policy(&base, &cybo, &zero).unwrap().ok);
+
+        for bad in [-0.1, f64::NAN] {
+            let policy = CoImpactPolicy::default().may_degrade("PM", bad);
+            assert!(matches!(
+                lca_ok_with_policy(&base, &cybo, &policy),
+                Err(EcosafetyError::InvalidConfig { ref field, .. }) if field == "PM"
+            ));
+        }
+    }
+
+    #[test]
+    fn test_must_improve_needs_a_strict_decrease() {
+        let mut base = scenario("B", FunctionalUnit::MswTon, ScenarioMode::StatusQuo, 480.0);
+        let mut cybo = scenario("C", FunctionalUnit::MswTon, ScenarioMode::Cybocinder, 320.0);
+        base.other_impacts.insert("acidification".to_string(), 1.5);
+        base.other_impacts.insert("noise".to_string(), 40.0);
+        let policy = CoImpactPolicy::default().must_improve("acidification");
+
+        let mut verdict = |value: f64| {
+            cybo.other_impacts
+                .insert("acidification".to_string(), value);
+            lca_ok_with_policy(&base, &cybo, &policy).unwrap()
+        };
+        let v = verdict(1.2);
+        assert!(v.ok && v.gwp_ok);
+        assert_eq!(v.categories[0].reason, "improved");
+        // Equal is not an improvement.
+        let v = verdict(1.5);
+        assert!(!v.ok);
+        assert_eq!(v.blocking()[0].category, "acidification");
+        assert!(!verdict(1.8).ok);
+        // Categories outside the policy are informational and never block.
+        let v = verdict(1.0);
+        assert!(v.ok);
+        assert_eq!(v.categories[1].category, "noise");
+        assert_eq!(
+            v.categories[1].requirement,
+            ImpactRequirement::Informational
+        );
+
+        // A must-improve category the baseline does not report fails.
+        base.other_impacts.remove("acidification");
+        let v = lca_ok_with_policy(&base, &cybo, &policy).unwrap();
+        assert!(!v.ok);
+        assert!(
+            v.blocking()[0].reason.contains("missing from B"),
+            "{}",
+            v.blocking()[0].reason
+        );
+    }
 }
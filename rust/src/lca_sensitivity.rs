@@ -0,0 +1,286 @@
+use crate::avoided_burden::{recompute_gwp, AvoidedBurdenModel};
+use crate::error::EcosafetyError;
+use crate::lca_gate::lca_ok;
+use crate::lca_uncertainty::LcaField;
+use crate::types::LcaScenario;
+
+/// Which scenario(s) of the pair a parameter perturbation applies to.
+/// Region-wide conditions such as grid intensity apply to both.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Side {
+    Base,
+    Cybo,
+    Both,
+}
+
+/// One parameter to sweep, with its plausible low/high values.
+#[derive(Clone, Debug)]
+pub struct SensitivityParam {
+    pub field: LcaField,
+    pub side: Side,
+    pub low: f64,
+    pub high: f64,
+}
+
+impl SensitivityParam {
+    /// Sweep `field` over ±`rel_span` of its nominal value in `nominal`,
+    /// kept within [0, 1] for fraction fields.
+    pub fn around(
+        field: LcaField,
+        side: Side,
+        nominal: &LcaScenario,
+        rel_span: f64,
+    ) -> Result<Self, EcosafetyError> {
+        let x = field
+            .get(nominal)
+            .ok_or_else(|| EcosafetyError::MissingLcaParameter {
+                scenario_id: nominal.scenario_id.clone(),
+                field: field.name(),
+            })?;
+        let (mut low, mut high) = (x - x.abs() * rel_span, x + x.abs() * rel_span);
+        if field.is_fraction() {
+            low = low.clamp(0.0, 1.0);
+            high = high.clamp(0.0, 1.0);
+        }
+        Ok(SensitivityParam {
+            field,
+            side,
+            low,
+            high,
+        })
+    }
+}
+
+/// One bar of the tornado chart: GWP_base − GWP_cybo at both ends.
+#[derive(Clone, Debug)]
+pub struct TornadoBar {
+    pub field: LcaField,
+    pub side: Side,
+    pub low: f64,
+    pub high: f64,
+    pub diff_at_low: f64,
+    pub diff_at_high: f64,
+    pub swing: f64,
+}
+
+/// Value of a parameter at which `lca_ok` flips. Only [low, high] is
+/// searched: when both ends agree the break-even, if any, lies outside the
+/// sweep and `value` is `None`.
+#[derive(Clone, Debug)]
+pub struct BreakEven {
+    pub field: LcaField,
+    pub side: Side,
+    pub ok_at_low: bool,
+    pub ok_at_high: bool,
+    pub value: Option<f64>,
+}
+
+impl BreakEven {
+    /// `lca_ok` is the same across the whole sweep.
+    pub fn outside_range(&self) -> bool {
+        self.value.is_none()
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct SensitivityReport {
+    /// GWP_base − GWP_cybo at nominal parameters (positive = cybocinder better).
+    pub diff_nominal: f64,
+    /// Bars sorted by descending swing.
+    pub tornado: Vec<TornadoBar>,
+    pub break_even: Vec<BreakEven>,
+}
+
+struct Evaluator<'a> {
+    base: &'a LcaScenario,
+    cybo: &'a LcaScenario,
+    model: &'a AvoidedBurdenModel,
+    furnace_direct_kgco2eq_per_ton: f64,
+}
+
+impl Evaluator<'_> {
+    /// Recomputed (GWP_base − GWP_cybo, lca_ok) with `field` set to `value`.
+    fn eval(&self, p: Option<(&SensitivityParam, f64)>) -> Result<(f64, bool), EcosafetyError> {
+        let mut b = self.base.clone();
+        let mut c = self.cybo.clone();
+        if let Some((p, value)) = p {
+            if p.side != Side::Cybo {
+                p.field.set(&mut b, value);
+            }
+            if p.side != Side::Base {
+                p.field.set(&mut c, value);
+            }
+        }
+        b.gwp_kg_co2eq =
+            recompute_gwp(&b, self.model, self.furnace_direct_kgco2eq_per_ton)?.net_kgco2eq;
+        c.gwp_kg_co2eq =
+            recompute_gwp(&c, self.model, self.furnace_direct_kgco2eq_per_ton)?.net_kgco2eq;
+        Ok((b.gwp_kg_co2eq - c.gwp_kg_co2eq, lca_ok(&b, &c)?))
+    }
+
+    /// Bisect [low, high] given the verdicts already computed at both ends.
+    fn break_even(
+        &self,
+        p: &SensitivityParam,
+        ok_at_low: bool,
+        ok_at_high: bool,
+    ) -> Result<BreakEven, EcosafetyError> {
+        let value = if ok_at_low == ok_at_high {
+            None
+        } else {
+            let (mut lo, mut hi) = (p.low, p.high);
+            for _ in 0..64 {
+                let mid = 0.5 * (lo + hi);
+                if self.eval(Some((p, mid)))?.1 == ok_at_low {
+                    lo = mid;
+                } else {
+                    hi = mid;
+                }
+            }
+            Some(0.5 * (lo + hi))
+        };
+        Ok(BreakEven {
+            field: p.field,
+            side: p.side,
+            ok_at_low,
+            ok_at_high,
+            value,
+        })
+    }
+}
+
+/// One-at-a-time sensitivity of the LCA gate. GWP is recomputed from the
+/// avoided-burden parameters (see `recompute_gwp`) so that every swept
+/// parameter actually moves the result; the stated `gwp_kg_co2eq` is ignored.
+pub fn lca_sensitivity(
+    base: &LcaScenario,
+    cybo: &LcaScenario,
+    params: &[SensitivityParam],
+    model: &AvoidedBurdenModel,
+    furnace_direct_kgco2eq_per_ton: f64,
+) -> Result<SensitivityReport, EcosafetyError> {
+    let ev = Evaluator {
+        base,
+        cybo,
+        model,
+        furnace_direct_kgco2eq_per_ton,
+    };
+    let (diff_nominal, _) = ev.eval(None)?;
+
+    let mut tornado = Vec::with_capacity(params.len());
+    let mut break_even = Vec::with_capacity(params.len());
+    for p in params {
+        if p.field == LcaField::Gwp {
+            return Err(EcosafetyError::InvalidConfig {
+                field: p.field.name().to_string(),
+                reason: "GWP is derived, sweep its avoided-burden inputs instead".to_string(),
+            });
+        }
+        let (diff_at_low, ok_at_low) = ev.eval(Some((p, p.low)))?;
+        let (diff_at_high, ok_at_high) = ev.eval(Some((p, p.high)))?;
+        tornado.push(TornadoBar {
+            field: p.field,
+            side: p.side,
+            low: p.low,
+            high: p.high,
+            diff_at_low,
+            diff_at_high,
+            swing: (diff_at_high - diff_at_low).abs(),
+        });
+        break_even.push(ev.break_even(p, ok_at_low, ok_at_high)?);
+    }
+    tornado.sort_by(|a, b| b.swing.total_cmp(&a.swing));
+
+    Ok(SensitivityReport {
+        diff_nominal,
+        tornado,
+        break_even,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{FunctionalUnit, ScenarioMode};
+    use std::collections::BTreeMap;
+
+    fn scenario(mode: ScenarioMode, landfill: f64) -> LcaScenario {
+        LcaScenario {
+            scenario_id: format!("{}", mode),
+            region_id: "PHX".to_string(),
+            functional_unit: FunctionalUnit::MswTon,
+            mode,
+            gwp_kg_co2eq: 0.0,
+            grid_gco2_per_kwh: 400.0,
+            landfill_ref_gwp_kgco2_per_ton: Some(landfill),
+            avoided_virgin_metal_kgco2eq_per_kg: Some(2.0),
+            energy_recovery_efficiency: Some(0.2),
+            recycling_rate: Some(0.25),
+            other_impacts: BTreeMap::new(),
+        }
+    }
+
+    fn param(field: LcaField, side: Side, low: f64, high: f64) -> SensitivityParam {
+        SensitivityParam {
+            field,
+            side,
+            low,
+            high,
+        }
+    }
+
+    // With 0.75 t treated per ton and 420 kWh recovered:
+    //   base = 0.75·L − 0.42·g,  cybo = 262.5 − 0.42·g − 18.75·m
+    // so diff = 0.75·L − 262.5 + 18.75·m, and the grid term cancels.
+
+    #[test]
+    fn test_tornado_orders_by_swing() {
+        let base = scenario(ScenarioMode::StatusQuo, 500.0);
+        let cybo = scenario(ScenarioMode::Cybocinder, 500.0);
+        let params = [
+            param(LcaField::GridIntensity, Side::Both, 300.0, 500.0),
+            param(LcaField::AvoidedVirginMetal, Side::Cybo, 1.0, 3.0),
+            param(LcaField::LandfillRefGwp, Side::Base, 200.0, 600.0),
+        ];
+        let model = AvoidedBurdenModel::default();
+        let report = lca_sensitivity(&base, &cybo, &params, &model, 350.0).unwrap();
+        assert!((report.diff_nominal - 150.0).abs() < 1e-9);
+
+        let order: Vec<LcaField> = report.tornado.iter().map(|b| b.field).collect();
+        assert_eq!(
+            order,
+            [
+                LcaField::LandfillRefGwp,
+                LcaField::AvoidedVirginMetal,
+                LcaField::GridIntensity
+            ]
+        );
+        let swings: Vec<f64> = report.tornado.iter().map(|b| b.swing).collect();
+        assert!((swings[0] - 300.0).abs() < 1e-9);
+        assert!((swings[1] - 37.5).abs() < 1e-9);
+        assert!(swings[2].abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_break_even_matches_hand_computed_value() {
+        let base = scenario(ScenarioMode::StatusQuo, 500.0);
+        let cybo = scenario(ScenarioMode::Cybocinder, 500.0);
+        let model = AvoidedBurdenModel::default();
+        let params = [
+            // diff = 0 at L = (262.5 − 37.5) / 0.75 = 300.
+            param(LcaField::LandfillRefGwp, Side::Base, 200.0, 600.0),
+            // Landfill never drops low enough here to flip the verdict.
+            param(LcaField::LandfillRefGwp, Side::Base, 400.0, 600.0),
+        ];
+        let report = lca_sensitivity(&base, &cybo, &params, &model, 350.0).unwrap();
+        let inside = &report.break_even[0];
+        assert!(!inside.ok_at_low && inside.ok_at_high);
+        assert!((inside.value.unwrap() - 300.0).abs() < 1e-6);
+        assert!(report.break_even[1].outside_range());
+
+        // 0.25 ± 2.5 is clamped to the whole fraction range.
+        let wide =
+            SensitivityParam::around(LcaField::RecyclingRate, Side::Both, &base, 10.0).unwrap();
+        assert_eq!((wide.low, wide.high), (0.0, 1.0));
+    }
+}
@@ -0,0 +1,257 @@
+use crate::error::EcosafetyError;
+use crate::lca_gate::{lca_ok, region_lca_gate, RegionLcaVerdict};
+use crate::telemetry_shard::{ShardError, ShardRow, ShardTable};
+use crate::types::{FunctionalUnit, LcaScenario, ScenarioMode};
+use std::collections::{BTreeMap, HashSet};
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+/// Baseline/candidate pair for one region and functional unit.
+#[derive(Clone, Debug)]
+pub struct LcaPair {
+    pub base: LcaScenario,
+    pub cybo: LcaScenario,
+}
+
+/// Outcome of `lca_ok` for one pair of a shard.
+#[derive(Clone, Debug)]
+pub struct LcaPairVerdict {
+    pub region_id: String,
+    pub functional_unit: FunctionalUnit,
+    pub base_scenario_id: String,
+    pub cybo_scenario_id: String,
+    pub ok: bool,
+}
+
+/// All scenarios of an LCA shard plus their baseline/candidate pairing.
+#[derive(Clone, Debug)]
+pub struct LcaShard {
+    pub scenarios: Vec<LcaScenario>,
+    pub pairs: Vec<LcaPair>,
+}
+
+impl LcaShard {
+    /// Run the primary LCA gate over every pair in the shard.
+    pub fn gate_all(&self) -> Result<Vec<LcaPairVerdict>, EcosafetyError> {
+        self.pairs
+            .iter()
+            .map(|p| {
+                Ok(LcaPairVerdict {
+                    region_id: p.base.region_id.clone(),
+                    functional_unit: p.base.functional_unit,
+                    base_scenario_id: p.base.scenario_id.clone(),
+                    cybo_scenario_id: p.cybo.scenario_id.clone(),
+                    ok: lca_ok(&p.base, &p.cybo)?,
+                })
+            })
+            .collect()
+    }
+
+    /// True only if the shard has at least one pair and every pair passes.
+    pub fn all_ok(&self) -> Result<bool, EcosafetyError> {
+        Ok(!self.pairs.is_empty() && self.gate_all()?.iter().all(|v| v.ok))
+    }
+
+    /// Multi-functional-unit deployment gate for one region of the shard.
+    pub fn region_gate(&self, region_id: &str) -> Result<RegionLcaVerdict, EcosafetyError> {
+        region_lca_gate(region_id, &self.scenarios)
+    }
+}
+
+fn optional_real(
+    row: &ShardRow,
+    col: Option<usize>,
+    field: &str,
+) -> Result<Option<f64>, ShardError> {
+    match col {
+        Some(c) => Ok(Some(row.real(c, field)?)),
+        None => Ok(None),
+    }
+}
+
+fn column(table: &ShardTable, names: &[&str]) -> Result<usize, ShardError> {
+    table.find(names).ok_or_else(|| ShardError::MissingColumn {
+        column: names[0].to_string(),
+    })
+}
+
+/// Parse an LCA scenario shard. Both layouts in `qpudatashards/` are accepted:
+///
+/// - root: scenario_id,region_id,functional_unit,mode,GWP_kgCO2eq,grid_gCO2_per_kWh,...
+/// - particles: scenarioid,region,grid_intensity_gco2_kwh,recycling_rate_frac,tech,func_unit,GWP_kgco2eq,...
+///
+/// Avoided-burden columns absent from a layout are left as `None`, and
+/// `recompute_gwp` refuses scenarios that lack a value it needs. Columns named
+/// `impact_<category>` populate `other_impacts` under `<category>`. Every
+/// CYBOCINDER scenario must have exactly one STATUS_QUO scenario for the same
+/// region and functional unit; a baseline may serve several candidates.
+pub fn parse_lca_shard<R: BufRead>(reader: R) -> Result<LcaShard, ShardError> {
+    let table = ShardTable::parse(reader)?;
+
+    let c_id = column(&table, &["scenario_id", "scenarioid"])?;
+    let c_region = column(&table, &["region_id", "region"])?;
+    let c_fu = column(&table, &["functional_unit", "func_unit"])?;
+    let c_mode = column(&table, &["mode", "tech"])?;
+    let c_gwp = column(&table, &["GWP_kgCO2eq"])?;
+    let c_grid = column(&table, &["grid_gCO2_per_kWh", "grid_intensity_gco2_kwh"])?;
+    let c_landfill = table.find(&["landfill_ref_GWP_kgCO2eq_per_ton"]);
+    let c_metal = table.find(&["avoided_virgin_metal_kgCO2eq_per_kg"]);
+    let c_eff = table.find(&["energy_recovery_efficiency"]);
+    let c_recycling = table.find(&["recycling_rate", "recycling_rate_frac"]);
+    let c_impacts = table.prefixed("impact_");
+
+    let mut scenarios = Vec::with_capacity(table.rows.len());
+    let mut ids = HashSet::new();
+    let mut baselines: BTreeMap<(String, FunctionalUnit), usize> = BTreeMap::new();
+
+    for row in &table.rows {
+        let scenario_id = row.text(c_id, "scenario_id")?.to_string();
+        if !ids.insert(scenario_id.clone()) {
+            return Err(row.invalid("scenario_id", &scenario_id, "unique scenario id"));
+        }
+        let raw_fu = row.text(c_fu, "functional_unit")?;
+        let functional_unit: FunctionalUnit = raw_fu.parse().map_err(|_| {
+            row.invalid(
+                "functional_unit",
+                raw_fu,
+                "MSW_TON, ENERGY_MWH or RESOURCE_KG",
+            )
+        })?;
+        let raw_mode = row.text(c_mode, "mode")?;
+        let mode: ScenarioMode = raw_mode
+            .parse()
+            .map_err(|_| row.invalid("mode", raw_mode, "STATUS_QUO or CYBOCINDER"))?;
+
+        let scenario = LcaScenario {
+            scenario_id,
+            region_id: row.text(c_region, "region_id")?.to_string(),
+            functional_unit,
+            mode,
+            gwp_kg_co2eq: row.real(c_gwp, "GWP_kgCO2eq")?,
+            grid_gco2_per_kwh: row.real(c_grid, "grid_gCO2_per_kWh")?,
+            landfill_ref_gwp_kgco2_per_ton: optional_real(
+                row,
+                c_landfill,
+                "landfill_ref_GWP_kgCO2eq_per_ton",
+            )?,
+            avoided_virgin_metal_kgco2eq_per_kg: optional_real(
+                row,
+                c_metal,
+                "avoided_virgin_metal_kgCO2eq_per_kg",
+            )?,
+            energy_recovery_efficiency: optional_real(row, c_eff, "energy_recovery_efficiency")?,
+            recycling_rate: optional_real(row, c_recycling, "recycling_rate")?,
+            other_impacts: c_impacts
+                .iter()
+                .map(|(name, c)| Ok((name.clone(), row.real(*c, name)?)))
+                .collect::<Result<_, ShardError>>()?,
+        };
+
+        if mode == ScenarioMode::StatusQuo {
+            let key = (scenario.region_id.clone(), scenario.functional_unit);
+            if baselines.insert(key, scenarios.len()).is_some() {
+                return Err(row.invalid(
+                    "mode",
+                    raw_mode,
+                    "a single STATUS_QUO scenario per region and functional unit",
+                ));
+            }
+        }
+        scenarios.push((row.line, scenario));
+    }
+
+    if scenarios.is_empty() {
+        return Err(ShardError::NoRows);
+    }
+
+    let mut pairs = Vec::new();
+    for (line, cybo) in scenarios
+        .iter()
+        .filter(|(_, s)| s.mode == ScenarioMode::Cybocinder)
+    {
+        let key = (cybo.region_id.clone(), cybo.functional_unit);
+        let base = match baselines.get(&key) {
+            Some(&idx) => scenarios[idx].1.clone(),
+            None => {
+                return Err(ShardError::InvalidField {
+                    line: *line,
+                    field: "mode".to_string(),
+                    value: cybo.mode.to_string(),
+                    expected: "a STATUS_QUO scenario for the same region and functional unit",
+                })
+            }
+        };
+        pairs.push(LcaPair {
+            base,
+            cybo: cybo.clone(),
+        });
+    }
+
+    Ok(LcaShard {
+        scenarios: scenarios.into_iter().map(|(_, s)| s).collect(),
+        pairs,
+    })
+}
+
+/// Load and validate an LCA scenario shard from disk.
+pub fn load_lca_shard<P: AsRef<Path>>(path: P) -> Result<LcaShard, ShardError> {
+    let file = File::open(path)?;
+    parse_lca_shard(BufReader::new(file))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::avoided_burden::{recompute_gwp, AvoidedBurdenModel};
+
+    const ROOT: &str = include_str!("../../qpudatashards/CybocinderPhoenixLCA2026v1.csv");
+    const PARTICLES: &str =
+        include_str!("../../qpudatashards/particles/CybocinderPhoenixLCA2026v1.csv");
+
+    #[test]
+    fn test_particles_layout_leaves_missing_columns_unset() {
+        let shard = parse_lca_shard(PARTICLES.as_bytes()).unwrap();
+        assert_eq!(shard.scenarios.len(), 3);
+        assert_eq!(shard.pairs.len(), 2);
+        assert!(shard.all_ok().unwrap());
+
+        let base = &shard.pairs[0].base;
+        assert_eq!(base.mode, ScenarioMode::StatusQuo);
+        assert_eq!(base.recycling_rate, Some(0.25));
+        assert_eq!(base.energy_recovery_efficiency, None);
+        assert_eq!(base.landfill_ref_gwp_kgco2_per_ton, None);
+
+        let err = recompute_gwp(base, &AvoidedBurdenModel::default(), 350.0).unwrap_err();
+        assert_eq!(
+            err,
+            EcosafetyError::MissingLcaParameter {
+                scenario_id: "PHX-BASE-2026".to_string(),
+                field: "energy_recovery_efficiency",
+            }
+        );
+    }
+
+    #[test]
+    fn test_root_layout_reads_avoided_burden_columns() {
+        assert!(matches!(
+            parse_lca_shard(ROOT.as_bytes()),
+            Err(ShardError::NoRows)
+        ));
+        let rows = format!(
+            "{}{}{}",
+            ROOT,
+            "PHX-B,PHX,MSW_TON,STATUS_QUO,480,420,700,2.0,0.0,0.25,\n",
+            "PHX-C,PHX,MSW_TON,CYBOCINDER,320,420,700,2.0,0.22,0.25,\n",
+        );
+        let shard = parse_lca_shard(rows.as_bytes()).unwrap();
+        let cybo = &shard.pairs[0].cybo;
+        assert_eq!(cybo.landfill_ref_gwp_kgco2_per_ton, Some(700.0));
+        assert_eq!(cybo.avoided_virgin_metal_kgco2eq_per_kg, Some(2.0));
+        assert_eq!(cybo.energy_recovery_efficiency, Some(0.22));
+        assert_eq!(cybo.recycling_rate, Some(0.25));
+        let model = AvoidedBurdenModel::default();
+        assert!(recompute_gwp(cybo, &model, 350.0).is_ok());
+        assert!(recompute_gwp(&shard.pairs[0].base, &model, 350.0).is_ok());
+    }
+}
@@ -0,0 +1,558 @@
+use crate::avoided_burden::{recompute_gwp, AvoidedBurdenModel};
+use crate::error::EcosafetyError;
+use crate::lca_gate::lca_ok;
+use crate::rng::SplitMix64;
+use crate::types::LcaScenario;
+use std::collections::BTreeMap;
+
+/// Sampling distribution for one scenario field.
+#[derive(Clone, Debug)]
+pub enum Distribution {
+    Normal {
+        mean: f64,
+        sd: f64,
+    },
+    /// `mu` and `sigma` of the underlying normal, i.e. of ln(x).
+    LogNormal {
+        mu: f64,
+        sigma: f64,
+    },
+    Triangular {
+        min: f64,
+        mode: f64,
+        max: f64,
+    },
+    Uniform {
+        min: f64,
+        max: f64,
+    },
+}
+
+/// Rejection-sampling budget for a bounded field before the distribution is
+/// declared to have too little mass within the field's range.
+const MAX_TRUNCATED_DRAWS: usize = 1000;
+
+impl Distribution {
+    fn validate(&self, field: &str) -> Result<(), EcosafetyError> {
+        let ok = match *self {
+            Distribution::Normal { mean, sd } => mean.is_finite() && sd.is_finite() && sd >= 0.0,
+            Distribution::LogNormal { mu, sigma } => {
+                mu.is_finite() && sigma.is_finite() && sigma >= 0.0
+            }
+            Distribution::Triangular { min, mode, max } => {
+                min.is_finite() && max.is_finite() && min <= mode && mode <= max
+            }
+            Distribution::Uniform { min, max } => min.is_finite() && max.is_finite() && min <= max,
+        };
+        if ok {
+            Ok(())
+        } else {
+            Err(EcosafetyError::InvalidConfig {
+                field: field.to_string(),
+                reason: format!("invalid distribution parameters {:?}", self),
+            })
+        }
+    }
+
+    /// Bounded distributions must lie within the field's range `[lo, hi]`;
+    /// Normal and LogNormal are truncated to it when sampled instead.
+    fn validate_range(&self, field: &str, (lo, hi): (f64, f64)) -> Result<(), EcosafetyError> {
+        let bounds = match *self {
+            Distribution::Triangular { min, max, .. } | Distribution::Uniform { min, max } => {
+                Some((min, max))
+            }
+            Distribution::Normal { .. } | Distribution::LogNormal { .. } => None,
+        };
+        match bounds {
+            Some((min, max)) if min < lo || max > hi => Err(EcosafetyError::InvalidConfig {
+                field: field.to_string(),
+                reason: format!("{:?} reaches outside [{}, {}]", self, lo, hi),
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn sample(&self, rng: &mut SplitMix64) -> f64 {
+        match *self {
+            Distribution::Normal { mean, sd } => mean + sd * rng.standard_normal(),
+            Distribution::LogNormal { mu, sigma } => (mu + sigma * rng.standard_normal()).exp(),
+            Distribution::Triangular { min, mode, max } => {
+                if max == min {
+                    return min;
+                }
+                let u = rng.next_f64();
+                let f = (mode - min) / (max - min);
+                if u < f {
+                    min + (u * (max - min) * (mode - min)).sqrt()
+                } else {
+                    max - ((1.0 - u) * (max - min) * (max - mode)).sqrt()
+                }
+            }
+            Distribution::Uniform { min, max } => rng.range(min, max),
+        }
+    }
+}
+
+/// `LcaScenario` fields that can carry a distribution.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LcaField {
+    Gwp,
+    GridIntensity,
+    LandfillRefGwp,
+    AvoidedVirginMetal,
+    EnergyRecoveryEfficiency,
+    RecyclingRate,
+}
+
+impl LcaField {
+    pub fn name(&self) -> &'static str {
+        match self {
+            LcaField::Gwp => "GWP_kgCO2eq",
+            LcaField::GridIntensity => "grid_gCO2_per_kWh",
+            LcaField::LandfillRefGwp => "landfill_ref_GWP_kgCO2eq_per_ton",
+            LcaField::AvoidedVirginMetal => "avoided_virgin_metal_kgCO2eq_per_kg",
+            LcaField::EnergyRecoveryEfficiency => "energy_recovery_efficiency",
+            LcaField::RecyclingRate => "recycling_rate",
+        }
+    }
+
+    /// Fields that are fractions and only meaningful within [0, 1].
+    pub fn is_fraction(&self) -> bool {
+        matches!(
+            self,
+            LcaField::EnergyRecoveryEfficiency | LcaField::RecyclingRate
+        )
+    }
+
+    /// Physically meaningful range of the field: fractions lie in [0, 1],
+    /// intensities and reference burdens are non-negative. A scenario's GWP
+    /// can be negative (net avoided burden) and has no range.
+    pub fn range(&self) -> Option<(f64, f64)> {
+        match self {
+            LcaField::Gwp => None,
+            LcaField::EnergyRecoveryEfficiency | LcaField::RecyclingRate => Some((0.0, 1.0)),
+            LcaField::GridIntensity | LcaField::LandfillRefGwp | LcaField::AvoidedVirginMetal => {
+                Some((0.0, f64::INFINITY))
+            }
+        }
+    }
+
+    /// Properties of the region and its markets rather than of the
+    /// treatment: both scenarios of a pair see the same draw.
+    pub fn is_regional(&self) -> bool {
+        matches!(
+            self,
+            LcaField::GridIntensity | LcaField::LandfillRefGwp | LcaField::AvoidedVirginMetal
+        )
+    }
+
+    /// One draw of `dist` for this field, truncated to `range()` by
+    /// redrawing, which keeps the distribution's shape inside it.
+    fn draw(&self, dist: &Distribution, rng: &mut SplitMix64) -> Result<f64, EcosafetyError> {
+        let (lo, hi) = match self.range() {
+            Some(r) => r,
+            None => return Ok(dist.sample(rng)),
+        };
+        for _ in 0..MAX_TRUNCATED_DRAWS {
+            let x = dist.sample(rng);
+            if (lo..=hi).contains(&x) {
+                return Ok(x);
+            }
+        }
+        Err(EcosafetyError::InvalidConfig {
+            field: self.name().to_string(),
+            reason: format!("{:?} has almost no mass in [{}, {}]", dist, lo, hi),
+        })
+    }
+
+    /// The field's value, `None` for an avoided-burden field the shard lacks.
+    pub fn get(&self, s: &LcaScenario) -> Option<f64> {
+        match self {
+            LcaField::Gwp => Some(s.gwp_kg_co2eq),
+            LcaField::GridIntensity => Some(s.grid_gco2_per_kwh),
+            LcaField::LandfillRefGwp => s.landfill_ref_gwp_kgco2_per_ton,
+            LcaField::AvoidedVirginMetal => s.avoided_virgin_metal_kgco2eq_per_kg,
+            LcaField::EnergyRecoveryEfficiency => s.energy_recovery_efficiency,
+            LcaField::RecyclingRate => s.recycling_rate,
+        }
+    }
+
+    pub fn set(&self, s: &mut LcaScenario, value: f64) {
+        match self {
+            LcaField::Gwp => s.gwp_kg_co2eq = value,
+            LcaField::GridIntensity => s.grid_gco2_per_kwh = value,
+            LcaField::LandfillRefGwp => s.landfill_ref_gwp_kgco2_per_ton = Some(value),
+            LcaField::AvoidedVirginMetal => s.avoided_virgin_metal_kgco2eq_per_kg = Some(value),
+            LcaField::EnergyRecoveryEfficiency => s.energy_recovery_efficiency = Some(value),
+            LcaField::RecyclingRate => s.recycling_rate = Some(value),
+        }
+    }
+}
+
+/// Distributions attached to one scenario; fields without one stay fixed.
+#[derive(Clone, Debug, Default)]
+pub struct ScenarioUncertainty {
+    pub fields: BTreeMap<LcaField, Distribution>,
+}
+
+impl ScenarioUncertainty {
+    pub fn with(mut self, field: LcaField, dist: Distribution) -> Self {
+        self.fields.insert(field, dist);
+        self
+    }
+
+    /// Regional fields are drawn from a stream derived from `shared` alone,
+    /// so the two scenarios of a sample, given the same `shared`, get the
+    /// same value from the same distribution (and perfectly correlated values
+    /// from different ones). Other fields draw from `rng`.
+    fn sample(
+        &self,
+        s: &LcaScenario,
+        shared: u64,
+        rng: &mut SplitMix64,
+    ) -> Result<LcaScenario, EcosafetyError> {
+        let mut out = s.clone();
+        for (field, dist) in &self.fields {
+            let x = if field.is_regional() {
+                let mut seed = SplitMix64::new(shared ^ *field as u64);
+                field.draw(dist, &mut SplitMix64::new(seed.next_u64()))?
+            } else {
+                field.draw(dist, rng)?
+            };
+            field.set(&mut out, x);
+        }
+        Ok(out)
+    }
+}
+
+/// Where each sample's GWP comes from.
+#[derive(Clone, Debug)]
+pub enum GwpSource {
+    /// Use the (possibly sampled) `gwp_kg_co2eq` field as-is.
+    Stated,
+    /// Recompute GWP from the sampled avoided-burden parameters.
+    Recomputed {
+        model: AvoidedBurdenModel,
+        furnace_direct_kgco2eq_per_ton: f64,
+    },
+}
+
+#[derive(Clone, Debug)]
+pub struct MonteCarloConfig {
+    pub samples: usize,
+    pub seed: u64,
+    /// Required probability that PrimaryLca holds, e.g. 0.95.
+    pub confidence: f64,
+    pub gwp_source: GwpSource,
+}
+
+#[derive(Clone, Debug)]
+pub struct MonteCarloReport {
+    pub samples: usize,
+    /// Fraction of samples in which GWP_cybo < GWP_base.
+    pub p_invariant_holds: f64,
+    pub confidence_required: f64,
+    pub deployment_ok: bool,
+    /// Mean of GWP_base − GWP_cybo (positive = cybocinder better).
+    pub mean_diff_kgco2eq: f64,
+    /// (percentile, GWP_base − GWP_cybo) at P5, P25, P50, P75, P95.
+    pub diff_percentiles: Vec<(f64, f64)>,
+}
+
+/// Linear-interpolated percentile of an ascending-sorted, non-empty slice.
+pub(crate) fn percentile(sorted: &[f64], pct: f64) -> f64 {
+    let pos = pct / 100.0 * (sorted.len() - 1) as f64;
+    let lo = pos.floor() as usize;
+    let hi = pos.ceil() as usize;
+    sorted[lo] + (sorted[hi] - sorted[lo]) * (pos - lo as f64)
+}
+
+/// Probability that the `lca-gates.cybo.aln` PrimaryLca invariant
+/// (GWP_cybo < GWP_base) holds under the attached distributions. Deployment
+/// passes only if that probability is strictly above `cfg.confidence`.
+/// The same seed and inputs always give the same report.
+///
+/// Draws are truncated to each field's range, so a sample never carries a
+/// negative intensity or a fraction outside [0, 1]. Regional fields (grid
+/// intensity, landfill reference, metal credit) are drawn once per sample
+/// for both scenarios; independent draws would overstate the spread of the
+/// gate decision.
+pub fn monte_carlo_lca(
+    base: &LcaScenario,
+    base_unc: &ScenarioUncertainty,
+    cybo: &LcaScenario,
+    cybo_unc: &ScenarioUncertainty,
+    cfg: &MonteCarloConfig,
+) -> Result<MonteCarloReport, EcosafetyError> {
+    if cfg.samples == 0 {
+        return Err(EcosafetyError::InvalidConfig {
+            field: "samples".to_string(),
+            reason: "at least one sample is required".to_string(),
+        });
+    }
+    if !(0.0..=1.0).contains(&cfg.confidence) {
+        return Err(EcosafetyError::InvalidConfig {
+            field: "confidence".to_string(),
+            reason: "must be within [0, 1]".to_string(),
+        });
+    }
+    for (field, dist) in base_unc.fields.iter().chain(cybo_unc.fields.iter()) {
+        dist.validate(field.name())?;
+        if let Some(range) = field.range() {
+            dist.validate_range(field.name(), range)?;
+        }
+    }
+    // Pairing errors are structural, not stochastic: surface them once.
+    lca_ok(base, cybo)?;
+
+    let mut rng = SplitMix64::new(cfg.seed);
+    let mut diffs = Vec::with_capacity(cfg.samples);
+    let mut holds = 0usize;
+    for _ in 0..cfg.samples {
+        let shared = rng.next_u64();
+        let mut b = base_unc.sample(base, shared, &mut rng)?;
+        let mut c = cybo_unc.sample(cybo, shared, &mut rng)?;
+        if let GwpSource::Recomputed {
+            model,
+            furnace_direct_kgco2eq_per_ton,
+        } = &cfg.gwp_source
+        {
+            b.gwp_kg_co2eq = recompute_gwp(&b, model, *furnace_direct_kgco2eq_per_ton)?.net_kgco2eq;
+            c.gwp_kg_co2eq = recompute_gwp(&c, model, *furnace_direct_kgco2eq_per_ton)?.net_kgco2eq;
+        }
+        if lca_ok(&b, &c)? {
+            holds += 1;
+        }
+        diffs.push(b.gwp_kg_co2eq - c.gwp_kg_co2eq);
+    }
+
+    let mean_diff_kgco2eq = diffs.iter().sum::<f64>() / diffs.len() as f64;
+    diffs.sort_by(|a, b| a.total_cmp(b));
+    let diff_percentiles = [5.0, 25.0, 50.0, 75.0, 95.0]
+        .iter()
+        .map(|&p| (p, percentile(&diffs, p)))
+        .collect();
+
+    let p_invariant_holds = holds as f64 / cfg.samples as f64;
+    Ok(MonteCarloReport {
+        samples: cfg.samples,
+        p_invariant_holds,
+        confidence_required: cfg.confidence,
+        deployment_ok: p_invariant_holds > cfg.confidence,
+        mean_diff_kgco2eq,
+        diff_percentiles,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{FunctionalUnit, ScenarioMode};
+
+    fn scenario(mode: ScenarioMode, gwp: f64) -> LcaScenario {
+        LcaScenario {
+            scenario_id: format!("{}", mode),
+            region_id: "PHX".to_string(),
+            functional_unit: FunctionalUnit::MswTon,
+            mode,
+            gwp_kg_co2eq: gwp,
+            grid_gco2_per_kwh: 420.0,
+            landfill_ref_gwp_kgco2_per_ton: Some(700.0),
+            avoided_virgin_metal_kgco2eq_per_kg: Some(2.0),
+            energy_recovery_efficiency: Some(0.2),
+            recycling_rate: Some(0.25),
+            other_impacts: BTreeMap::new(),
+        }
+    }
+
+    fn config(seed: u64) -> MonteCarloConfig {
+        MonteCarloConfig {
+            samples: 2000,
+            seed,
+            confidence: 0.9,
+            gwp_source: GwpSource::Recomputed {
+                model: AvoidedBurdenModel::default(),
+                furnace_direct_kgco2eq_per_ton: 350.0,
+            },
+        }
+    }
+
+    #[test]
+    fn test_same_seed_same_report() {
+        let base = scenario(ScenarioMode::StatusQuo, 480.0);
+        let cybo = scenario(ScenarioMode::Cybocinder, 60.0);
+        let unc = ScenarioUncertainty::default()
+            .with(
+                LcaField::GridIntensity,
+                Distribution::Normal {
+                    mean: 420.0,
+                    sd: 80.0,
+                },
+            )
+            .with(
+                LcaField::RecyclingRate,
+                Distribution::Triangular {
+                    min: 0.15,
+                    mode: 0.25,
+                    max: 0.35,
+                },
+            );
+        let run = |seed| monte_carlo_lca(&base, &unc, &cybo, &unc, &config(seed)).unwrap();
+        let (a, b, c) = (run(7), run(7), run(8));
+        assert_eq!(a.p_invariant_holds, b.p_invariant_holds);
+        assert_eq!(a.mean_diff_kgco2eq, b.mean_diff_kgco2eq);
+        assert_eq!(a.diff_percentiles, b.diff_percentiles);
+        assert_ne!(a.mean_diff_kgco2eq, c.mean_diff_kgco2eq);
+    }
+
+    #[test]
+    fn test_wide_fraction_distribution_is_truncated() {
+        let base = scenario(ScenarioMode::StatusQuo, 480.0);
+        let cybo = scenario(ScenarioMode::Cybocinder, 60.0);
+        // Most of this mass lies outside [0, 1]; untruncated draws would make
+        // recompute_gwp refuse the sample.
+        let wide = ScenarioUncertainty::default()
+            .with(
+                LcaField::RecyclingRate,
+                Distribution::Normal {
+                    mean: 0.25,
+                    sd: 2.0,
+                },
+            )
+            .with(
+                LcaField::EnergyRecoveryEfficiency,
+                Distribution::LogNormal {
+                    mu: -1.6,
+                    sigma: 3.0,
+                },
+            );
+        let report = monte_carlo_lca(&base, &wide, &cybo, &wide, &config(1)).unwrap();
+        assert_eq!(report.samples, 2000);
+
+        let mut rng = SplitMix64::new(3);
+        let normal = Distribution::Normal {
+            mean: 0.25,
+            sd: 2.0,
+        };
+        for _ in 0..100 {
+            let x = LcaField::RecyclingRate.draw(&normal, &mut rng).unwrap();
+            assert!((0.0..=1.0).contains(&x));
+        }
+        let far = Distribution::Normal {
+            mean: 50.0,
+            sd: 1.0,
+        };
+        assert!(LcaField::RecyclingRate.draw(&far, &mut rng).is_err());
+
+        let uniform = ScenarioUncertainty::default().with(
+            LcaField::RecyclingRate,
+            Distribution::Uniform {
+                min: -0.5,
+                max: 1.5,
+            },
+        );
+        assert!(matches!(
+            monte_carlo_lca(&base, &uniform, &cybo, &wide, &config(1)),
+            Err(EcosafetyError::InvalidConfig { ref field, .. }) if field == "recycling_rate"
+        ));
+    }
+
+    #[test]
+    fn test_physical_quantities_are_truncated_at_zero() {
+        let base = scenario(ScenarioMode::StatusQuo, 480.0);
+        let cybo = scenario(ScenarioMode::Cybocinder, 60.0);
+        // A third of this mass is negative; recompute_gwp rejects a negative
+        // grid intensity, so untruncated draws would abort the run.
+        let unc = ScenarioUncertainty::default()
+            .with(
+                LcaField::GridIntensity,
+                Distribution::Normal {
+                    mean: 100.0,
+                    sd: 250.0,
+                },
+            )
+            .with(
+                LcaField::LandfillRefGwp,
+                Distribution::Normal {
+                    mean: 100.0,
+                    sd: 500.0,
+                },
+            );
+        let report = monte_carlo_lca(&base, &unc, &cybo, &unc, &config(5)).unwrap();
+        assert_eq!(report.samples, 2000);
+
+        let mut rng = SplitMix64::new(9);
+        let dist = &unc.fields[&LcaField::LandfillRefGwp];
+        for _ in 0..200 {
+            assert!(LcaField::LandfillRefGwp.draw(dist, &mut rng).unwrap() >= 0.0);
+        }
+        // GWP itself may be negative and is not truncated.
+        let gwp = Distribution::Normal {
+            mean: -50.0,
+            sd: 1.0,
+        };
+        assert!(LcaField::Gwp.draw(&gwp, &mut rng).unwrap() < 0.0);
+
+        let below_zero = ScenarioUncertainty::default().with(
+            LcaField::AvoidedVirginMetal,
+            Distribution::Uniform {
+                min: -1.0,
+                max: 3.0,
+            },
+        );
+        assert!(matches!(
+            monte_carlo_lca(&base, &below_zero, &cybo, &unc, &config(1)),
+            Err(EcosafetyError::InvalidConfig { ref field, .. })
+                if field == "avoided_virgin_metal_kgCO2eq_per_kg"
+        ));
+    }
+
+    #[test]
+    fn test_regional_fields_are_shared_within_a_sample() {
+        let base = scenario(ScenarioMode::StatusQuo, 480.0);
+        let cybo = scenario(ScenarioMode::Cybocinder, 60.0);
+        let unc = ScenarioUncertainty::default()
+            .with(
+                LcaField::GridIntensity,
+                Distribution::Normal {
+                    mean: 420.0,
+                    sd: 80.0,
+                },
+            )
+            .with(
+                LcaField::RecyclingRate,
+                Distribution::Uniform { min: 0.1, max: 0.4 },
+            );
+        let mut rng = SplitMix64::new(11);
+        for _ in 0..50 {
+            let shared = rng.next_u64();
+            let b = unc.sample(&base, shared, &mut rng).unwrap();
+            let c = unc.sample(&cybo, shared, &mut rng).unwrap();
+            assert_eq!(b.grid_gco2_per_kwh, c.grid_gco2_per_kwh);
+            assert_ne!(b.recycling_rate, c.recycling_rate);
+        }
+
+        // Both sides recover the same energy here, so a shared grid credit
+        // cancels out of the difference; drawn independently it would not.
+        let spread = |r: &MonteCarloReport| r.diff_percentiles[4].1 - r.diff_percentiles[0].1;
+        let grid_only = ScenarioUncertainty::default().with(
+            LcaField::GridIntensity,
+            unc.fields[&LcaField::GridIntensity].clone(),
+        );
+        let shared = monte_carlo_lca(&base, &grid_only, &cybo, &grid_only, &config(3)).unwrap();
+        let mut rng = SplitMix64::new(3);
+        let mut diffs: Vec<f64> = (0..2000)
+            .map(|_| {
+                let model = AvoidedBurdenModel::default();
+                let b = grid_only.sample(&base, rng.next_u64(), &mut rng).unwrap();
+                let c = grid_only.sample(&cybo, rng.next_u64(), &mut rng).unwrap();
+                recompute_gwp(&b, &model, 350.0).unwrap().net_kgco2eq
+                    - recompute_gwp(&c, &model, 350.0).unwrap().net_kgco2eq
+            })
+            .collect();
+        diffs.sort_by(|a, b| a.total_cmp(b));
+        let independent = percentile(&diffs, 95.0) - percentile(&diffs, 5.0);
+        assert!(spread(&shared).abs() < 1e-9, "{}", spread(&shared));
+        assert!(independent > 10.0, "{}", independent);
+    }
+}
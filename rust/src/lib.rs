@@ -1,5 +1,25 @@
+pub mod aln;
+pub mod aln_interp;
+pub mod contracts;
+pub mod error;
 pub mod types;
+pub mod units;
 pub mod lyapunov;
+pub mod residual_form;
+pub mod residual_groups;
+pub mod robust_residual;
+pub mod residual_window;
+pub mod lyapunov_controller;
+pub mod control_layers;
 pub mod lca_gate;
+pub mod avoided_burden;
+pub mod lca_uncertainty;
+pub mod lca_sensitivity;
 pub mod gates;
+pub mod dual_threshold;
+pub mod pilot_corridor;
+pub mod operating_mode;
+pub mod rng;
 pub mod telemetry_shard;
+pub mod corridor_shard;
+pub mod lca_shard;
@@ -1,5 +1,7 @@
-use crate::types::{Parameter, RiskCoordinateDef};
-use std::collections::HashMap;
+use crate::contracts::cybocinder_lyapunov as contract;
+use crate::error::EcosafetyError;
+use crate::types::{Direction, Parameter, RiskCoordinateDef};
+use crate::units::Unit;
 
 #[derive(Clone, Debug)]
 pub struct RiskCoord {
@@ -14,35 +16,189 @@ pub struct ResidualState {
 }
 
 fn clip01(x: f64) -> f64 {
-    if x < 0.0 { 0.0 } else if x > 1.0 { 1.0 } else { x }
+    x.clamp(0.0, 1.0)
 }
 
 pub fn compute_risk_coord(
     param: &Parameter,
     rc_def: &RiskCoordinateDef,
     x: f64,
-) -> RiskCoord {
+) -> Result<RiskCoord, EcosafetyError> {
     let denom = rc_def.r_max - rc_def.r_min;
-    assert!(denom > 0.0, "invalid r_min/r_max");
-    let raw = if param.direction_max {
-        (x - rc_def.r_min) / denom
-    } else {
-        (rc_def.r_max - x) / denom
+    if denom.is_nan() || denom <= 0.0 {
+        return Err(EcosafetyError::InvalidNormalization {
+            coord_id: rc_def.id,
+            param_name: rc_def.param_name.clone(),
+            r_min: rc_def.r_min,
+            r_max: rc_def.r_max,
+        });
+    }
+    if !rc_def.weight_w.is_finite() {
+        return Err(EcosafetyError::NonFiniteWeight {
+            coord_id: rc_def.id,
+            param_name: rc_def.param_name.clone(),
+            weight: rc_def.weight_w,
+        });
+    }
+    if rc_def.weight_w < 0.0 {
+        return Err(EcosafetyError::NegativeWeight {
+            coord_id: rc_def.id,
+            param_name: rc_def.param_name.clone(),
+            weight: rc_def.weight_w,
+        });
+    }
+    let raw = match param.direction {
+        Direction::Max => (x - rc_def.r_min) / denom,
+        Direction::Min => (rc_def.r_max - x) / denom,
     };
     let r = clip01(raw);
-    assert!(rc_def.weight_w >= 0.0, "negative weight");
-    RiskCoord { r, w: rc_def.weight_w }
+    Ok(RiskCoord { r, w: rc_def.weight_w })
+}
+
+/// A reading `x` in `unit`, converted into the parameter's unit. An unknown,
+/// ambiguous or dimensionally incompatible unit is refused rather than
+/// silently used.
+pub fn to_param_unit(param: &Parameter, x: f64, unit: &str) -> Result<f64, EcosafetyError> {
+    let mismatch = || EcosafetyError::UnitMismatch {
+        param_name: param.name.clone(),
+        expected: param.unit.clone(),
+        found: unit.to_string(),
+    };
+    let from = Unit::parse(unit).map_err(|_| mismatch())?;
+    let to = Unit::parse(&param.unit).map_err(|_| mismatch())?;
+    from.convert(x, &to).map_err(|_| mismatch())
+}
+
+/// Like `compute_risk_coord`, for a reading `x` expressed in `unit` (see
+/// `to_param_unit`).
+pub fn compute_risk_coord_measured(
+    param: &Parameter,
+    rc_def: &RiskCoordinateDef,
+    x: f64,
+    unit: &str,
+) -> Result<RiskCoord, EcosafetyError> {
+    compute_risk_coord(param, rc_def, to_param_unit(param, x, unit)?)
 }
 
-pub fn compute_residual(coords: &[RiskCoord]) -> ResidualState {
-    assert!(!coords.is_empty(), "no risk coordinates (no corridor -> no deployment)");
-    let mut v = 0.0;
-    for c in coords {
-        v += c.r * c.w;
+/// V_next of `spec/corridors.cybo.aln`, evaluated by the generated contract.
+/// The contract fixes the linear form V = Σ w·r, so this is the linear-only
+/// entry point; a corridor that declares another form goes through
+/// `residual_form::compute_residual_with(corridors.form(), ..)`.
+pub fn compute_residual(coords: &[RiskCoord]) -> Result<ResidualState, EcosafetyError> {
+    if coords.is_empty() {
+        return Err(EcosafetyError::NoCorridor);
     }
-    ResidualState { coords: coords.to_vec(), v }
+    // EPS and V_prev only enter the invariant, not V_next.
+    let k = contract::Constants {
+        eps: 0.0,
+        num_channels: coords.len() as f64,
+    };
+    let inp = contract::Inputs {
+        r_x: coords.iter().map(|c| c.r).collect(),
+        w: coords.iter().map(|c| c.w).collect(),
+        v_prev: 0.0,
+    };
+    let v = contract::compute(&k, &inp)
+        .map_err(|error| EcosafetyError::Contract {
+            contract: contract::CONTRACT,
+            error,
+        })?
+        .v_next;
+    Ok(ResidualState { coords: coords.to_vec(), v })
 }
 
+/// INVARIANT NonIncreasingResidual of the generated contract.
 pub fn is_admissible(v_prev: f64, v_next: f64, eps: f64) -> bool {
-    v_next <= v_prev + eps
+    // The invariant reads no channel arrays; they stay empty.
+    let k = contract::Constants {
+        eps,
+        num_channels: 0.0,
+    };
+    let inp = contract::Inputs {
+        r_x: Vec::new(),
+        w: Vec::new(),
+        v_prev,
+    };
+    // Scalar comparison only; should it ever fail, refuse the step.
+    contract::check_non_increasing_residual(&k, &inp, &contract::Outputs { v_next })
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn nox() -> (Parameter, RiskCoordinateDef) {
+        let param = Parameter {
+            name: "NOx".to_string(),
+            unit: "mg/Nm3".to_string(),
+            domain_min: 0.0,
+            domain_max: f64::INFINITY,
+            legal_limit: Some(200.0),
+            gold_limit: Some(150.0),
+            direction: Direction::Max,
+        };
+        let rc = RiskCoordinateDef {
+            id: 3,
+            param_name: "NOx".to_string(),
+            r_min: 0.0,
+            r_max: 200.0,
+            weight_w: 0.5,
+            channel: 0,
+        };
+        (param, rc)
+    }
+
+    #[test]
+    fn test_bad_weights_are_errors() {
+        let (param, mut rc) = nox();
+        assert_eq!(compute_risk_coord(&param, &rc, 100.0).unwrap().r, 0.5);
+
+        rc.weight_w = -0.1;
+        assert!(matches!(
+            compute_risk_coord(&param, &rc, 100.0),
+            Err(EcosafetyError::NegativeWeight { coord_id: 3, .. })
+        ));
+        for w in [f64::NAN, f64::INFINITY] {
+            rc.weight_w = w;
+            assert!(matches!(
+                compute_risk_coord(&param, &rc, 100.0),
+                Err(EcosafetyError::NonFiniteWeight { coord_id: 3, .. })
+            ));
+        }
+
+        rc.weight_w = 0.5;
+        rc.r_max = rc.r_min;
+        assert!(matches!(
+            compute_risk_coord(&param, &rc, 100.0),
+            Err(EcosafetyError::InvalidNormalization { .. })
+        ));
+    }
+
+    #[test]
+    fn test_measured_reading_needs_a_known_compatible_unit() {
+        let (param, rc) = nox();
+        let r = compute_risk_coord_measured(&param, &rc, 100_000.0, "ug/Nm3").unwrap();
+        assert!((r.r - 0.5).abs() < 1e-12);
+        for unit in ["%", "furlong", "ug/m3"] {
+            assert_eq!(
+                compute_risk_coord_measured(&param, &rc, 1.0, unit).unwrap_err(),
+                EcosafetyError::UnitMismatch {
+                    param_name: "NOx".to_string(),
+                    expected: "mg/Nm3".to_string(),
+                    found: unit.to_string(),
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn test_empty_corridor_has_no_residual() {
+        assert_eq!(
+            compute_residual(&[]).unwrap_err(),
+            EcosafetyError::NoCorridor
+        );
+        let v = compute_residual(&[RiskCoord { r: 0.5, w: 0.4 }]).unwrap().v;
+        assert!((v - 0.2).abs() < 1e-12);
+    }
 }
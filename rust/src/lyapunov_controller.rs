@@ -0,0 +1,394 @@
+use crate::corridor_shard::CorridorSet;
+use crate::dual_threshold::{evaluate_thresholds, Measurement};
+use crate::error::EcosafetyError;
+use crate::lyapunov::{is_admissible, ResidualState};
+use crate::residual_groups::{group_residual, GroupLimit, ResidualGroup, SubResidual};
+use crate::robust_residual::{robust_report, UncertaintyBounds};
+
+/// Plant prediction model used to judge a proposed control move. Implemented
+/// by the DCS/PLC integration (first-principles kernel, DTW/JITL surrogate, ...).
+pub trait PlantModel {
+    type State;
+    type Move;
+
+    /// Predicted measurements x(t+1) if `mv` is applied in `state`.
+    fn predict(
+        &self,
+        state: &Self::State,
+        mv: &Self::Move,
+    ) -> Result<Vec<Measurement>, EcosafetyError>;
+}
+
+/// Why a move was accepted or rejected.
+#[derive(Clone, Debug, PartialEq)]
+pub enum MoveReason {
+    Admissible,
+    /// The prediction does not cover a corridor parameter.
+    MissingPrediction {
+        param_name: String,
+        channel: u32,
+    },
+    /// Predicted r ≥ 1 (r_hi under uncertainty): hard corridor violation
+    /// (grammar E.2 rule 3).
+    HardCorridorViolation {
+        param_name: String,
+        r: f64,
+    },
+    LegalLimitExceeded {
+        param_name: String,
+        value: f64,
+    },
+    /// V_{t+1} > V_t + ε.
+    ResidualIncrease {
+        v_t: f64,
+        v_next: f64,
+        eps: f64,
+    },
+    /// V_g,{t+1} > V_g,t + ε_g for a group with its own limit.
+    GroupResidualIncrease {
+        group: ResidualGroup,
+        prev: f64,
+        next: f64,
+        eps: f64,
+    },
+    /// A group limit is configured but no previous sub-residual was given.
+    MissingGroupBaseline {
+        group: ResidualGroup,
+    },
+    /// The prediction could not be evaluated; rejected fail-closed.
+    Error(EcosafetyError),
+}
+
+#[derive(Clone, Debug)]
+pub struct MoveDecision {
+    pub admissible: bool,
+    pub reason: MoveReason,
+    /// Predicted residual, when it could be computed.
+    pub predicted: Option<ResidualState>,
+    /// Predicted sub-residuals; the baseline for the next grouped check.
+    pub groups: Vec<SubResidual>,
+}
+
+impl MoveDecision {
+    fn reject(
+        reason: MoveReason,
+        predicted: Option<ResidualState>,
+        groups: Vec<SubResidual>,
+    ) -> Self {
+        MoveDecision {
+            admissible: false,
+            reason,
+            predicted,
+            groups,
+        }
+    }
+}
+
+/// Lyapunov-gated control interface: the single entry point PLC/DCS
+/// frontends call before applying a move.
+pub struct LyapunovController<P: PlantModel> {
+    pub corridors: CorridorSet,
+    pub plant: P,
+    pub eps: f64,
+    /// Per-group limits checked in addition to the total residual.
+    pub group_limits: Vec<GroupLimit>,
+    /// Sensor error bounds. When set, every check below is made on the worst
+    /// case of the predicted interval (r_hi, unfavourable value, V_hi), and
+    /// `v_t` / `groups_t` should be V_lo / `groups_lo` of the current
+    /// measurements (`RobustReport`).
+    pub uncertainty: Option<UncertaintyBounds>,
+}
+
+impl<P: PlantModel> LyapunovController<P> {
+    pub fn new(corridors: CorridorSet, plant: P, eps: f64) -> Self {
+        LyapunovController {
+            corridors,
+            plant,
+            eps,
+            group_limits: Vec::new(),
+            uncertainty: None,
+        }
+    }
+
+    pub fn with_uncertainty(mut self, bounds: UncertaintyBounds) -> Self {
+        self.uncertainty = Some(bounds);
+        self
+    }
+
+    pub fn with_group_limit(mut self, limit: GroupLimit) -> Self {
+        self.group_limits.push(limit);
+        self
+    }
+
+    /// `is_admissible_move(State, Move, V_t)` from grammar §2.1 / E.2: predict
+    /// x(t+1), recompute the residual over the shared corridor definition and
+    /// accept only if every corridor parameter is predicted, all r < 1, all
+    /// legal limits hold and V_{t+1} ≤ V_t + ε.
+    ///
+    /// Group limits need the previous sub-residuals, which this entry point
+    /// does not have: with any limit configured the move is rejected, and
+    /// callers should use `is_admissible_move_grouped`.
+    pub fn is_admissible_move(&self, state: &P::State, mv: &P::Move, v_t: f64) -> MoveDecision {
+        self.is_admissible_move_grouped(state, mv, v_t, &[])
+    }
+
+    /// `is_admissible_move` plus every configured group limit, judged against
+    /// the sub-residuals at time t (e.g. `DualThresholdReport::groups` of the
+    /// current measurements, or `MoveDecision::groups` of the applied move).
+    pub fn is_admissible_move_grouped(
+        &self,
+        state: &P::State,
+        mv: &P::Move,
+        v_t: f64,
+        groups_t: &[SubResidual],
+    ) -> MoveDecision {
+        let predicted = match self.plant.predict(state, mv) {
+            Ok(m) => m,
+            Err(e) => return MoveDecision::reject(MoveReason::Error(e), None, Vec::new()),
+        };
+        let report = match evaluate_thresholds(&self.corridors, &predicted) {
+            Ok(r) => r,
+            Err(e) => return MoveDecision::reject(MoveReason::Error(e), None, Vec::new()),
+        };
+        let robust = match &self.uncertainty {
+            None => None,
+            Some(bounds) => match robust_report(&self.corridors, &report, bounds) {
+                Ok(r) => Some(r),
+                Err(e) => return MoveDecision::reject(MoveReason::Error(e), None, Vec::new()),
+            },
+        };
+        let residual = report.residual.clone();
+        let groups = report.groups.clone();
+
+        for (i, s) in report.statuses.iter().enumerate() {
+            let worst = match &robust {
+                None => s.value.zip(s.r).map(|(value, r)| (value, r, s.legal_ok)),
+                Some(rr) => {
+                    let rs = &rr.statuses[i];
+                    rs.worst_value
+                        .zip(rs.r)
+                        .map(|(value, r)| (value, r.hi, rs.legal_ok))
+                }
+            };
+            let (value, r, legal_ok) = match worst {
+                Some(w) => w,
+                _ => {
+                    return MoveDecision::reject(
+                        MoveReason::MissingPrediction {
+                            param_name: s.param_name.clone(),
+                            channel: s.channel,
+                        },
+                        residual,
+                        groups,
+                    )
+                }
+            };
+            if r >= 1.0 {
+                return MoveDecision::reject(
+                    MoveReason::HardCorridorViolation {
+                        param_name: s.param_name.clone(),
+                        r,
+                    },
+                    residual,
+                    groups,
+                );
+            }
+            if !legal_ok {
+                return MoveDecision::reject(
+                    MoveReason::LegalLimitExceeded {
+                        param_name: s.param_name.clone(),
+                        value,
+                    },
+                    residual,
+                    groups,
+                );
+            }
+        }
+
+        // Every corridor parameter was predicted, so the residual exists.
+        let v_next = match &robust {
+            None => residual.as_ref().map(|r| r.v),
+            Some(rr) => rr.residual.map(|v| v.hi),
+        }
+        .unwrap_or(f64::INFINITY);
+        let groups_next = robust.as_ref().map_or(&groups, |rr| &rr.groups_hi);
+        if !is_admissible(v_t, v_next, self.eps) {
+            return MoveDecision::reject(
+                MoveReason::ResidualIncrease {
+                    v_t,
+                    v_next,
+                    eps: self.eps,
+                },
+                residual,
+                groups,
+            );
+        }
+
+        for limit in &self.group_limits {
+            let prev = match group_residual(groups_t, &limit.group) {
+                Some(v) => v,
+                None => {
+                    return MoveDecision::reject(
+                        MoveReason::MissingGroupBaseline {
+                            group: limit.group.clone(),
+                        },
+                        residual,
+                        groups,
+                    )
+                }
+            };
+            let next = group_residual(groups_next, &limit.group).unwrap_or(f64::INFINITY);
+            if !is_admissible(prev, next, limit.eps) {
+                return MoveDecision::reject(
+                    MoveReason::GroupResidualIncrease {
+                        group: limit.group.clone(),
+                        prev,
+                        next,
+                        eps: limit.eps,
+                    },
+                    residual,
+                    groups,
+                );
+            }
+        }
+
+        MoveDecision {
+            admissible: true,
+            reason: MoveReason::Admissible,
+            predicted: residual,
+            groups,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::corridor_shard::parse_corridor_shard;
+
+    const CORRIDORS: &str = "\
+node_id,parameter,unit,legal_limit,gold_limit,r_min,r_max,weight_w,channel
+PHX-TEST,NOx,mg/Nm3,200,150,0,250,0.5,0
+PHX-TEST,CO,mg/Nm3,50,10,0,50,0.5,1
+";
+
+    /// Predicts whatever the move says; `None` stands for a model failure.
+    struct Replay;
+
+    impl PlantModel for Replay {
+        type State = ();
+        type Move = Option<Vec<Measurement>>;
+
+        fn predict(
+            &self,
+            _: &(),
+            mv: &Option<Vec<Measurement>>,
+        ) -> Result<Vec<Measurement>, EcosafetyError> {
+            mv.clone().ok_or(EcosafetyError::InvalidConfig {
+                field: "plant".to_string(),
+                reason: "surrogate out of its training range".to_string(),
+            })
+        }
+    }
+
+    fn mv(readings: &[(&str, u32, f64)]) -> Option<Vec<Measurement>> {
+        Some(
+            readings
+                .iter()
+                .map(|&(name, channel, value)| Measurement {
+                    param_name: name.to_string(),
+                    channel,
+                    value,
+                    unit: "mg/Nm3".to_string(),
+                })
+                .collect(),
+        )
+    }
+
+    fn controller() -> LyapunovController<Replay> {
+        LyapunovController::new(
+            parse_corridor_shard(CORRIDORS.as_bytes()).unwrap(),
+            Replay,
+            0.0,
+        )
+    }
+
+    #[test]
+    fn test_each_reason_for_rejecting_a_move() {
+        let c = controller();
+        // V = 0.5·100/250 + 0.5·10/50 = 0.3.
+        let ok = c.is_admissible_move(&(), &mv(&[("NOx", 0, 100.0), ("CO", 1, 10.0)]), 0.31);
+        assert_eq!(ok.reason, MoveReason::Admissible);
+        assert!(ok.admissible);
+        assert!((ok.predicted.unwrap().v - 0.3).abs() < 1e-12);
+
+        let d = c.is_admissible_move(&(), &mv(&[("NOx", 0, 100.0)]), 1.0);
+        assert_eq!(
+            d.reason,
+            MoveReason::MissingPrediction {
+                param_name: "CO".to_string(),
+                channel: 1,
+            }
+        );
+
+        let d = c.is_admissible_move(&(), &mv(&[("NOx", 0, 260.0), ("CO", 1, 10.0)]), 1.0);
+        assert!(matches!(d.reason, MoveReason::HardCorridorViolation { r, .. } if r == 1.0));
+
+        // Inside the normalisation band (r = 0.84) but over the legal 200.
+        let d = c.is_admissible_move(&(), &mv(&[("NOx", 0, 210.0), ("CO", 1, 10.0)]), 1.0);
+        assert_eq!(
+            d.reason,
+            MoveReason::LegalLimitExceeded {
+                param_name: "NOx".to_string(),
+                value: 210.0,
+            }
+        );
+
+        let d = c.is_admissible_move(&(), &mv(&[("NOx", 0, 100.0), ("CO", 1, 10.0)]), 0.25);
+        assert!(!d.admissible);
+        assert!(matches!(d.reason, MoveReason::ResidualIncrease { .. }));
+        assert!(d.predicted.is_some());
+    }
+
+    #[test]
+    fn test_group_limits_and_failures_reject_fail_closed() {
+        let co = ResidualGroup::Channel(1);
+        let c = controller().with_group_limit(GroupLimit::never_increase(co.clone()));
+        let now = c.is_admissible_move_grouped(
+            &(),
+            &mv(&[("NOx", 0, 150.0), ("CO", 1, 10.0)]),
+            1.0,
+            &[SubResidual {
+                group: co.clone(),
+                v: Some(1.0),
+            }],
+        );
+        assert!(now.admissible, "{:?}", now.reason);
+
+        // NOx falls more than CO rises: V drops, but the CO channel may not.
+        let up = mv(&[("NOx", 0, 50.0), ("CO", 1, 20.0)]);
+        let d = c.is_admissible_move_grouped(&(), &up, 0.4, &now.groups);
+        assert!(matches!(
+            d.reason,
+            MoveReason::GroupResidualIncrease { ref group, .. } if *group == co
+        ));
+        let d = c.is_admissible_move(&(), &up, 0.4);
+        assert_eq!(d.reason, MoveReason::MissingGroupBaseline { group: co });
+
+        let d = c.is_admissible_move(&(), &None, f64::INFINITY);
+        assert!(!d.admissible);
+        assert!(matches!(
+            d.reason,
+            MoveReason::Error(EcosafetyError::InvalidConfig { .. })
+        ));
+        assert!(d.predicted.is_none());
+
+        let mut bad_unit = mv(&[("NOx", 0, 100.0), ("CO", 1, 10.0)]);
+        bad_unit.as_mut().unwrap()[1].unit = "ppm".to_string();
+        let d = c.is_admissible_move(&(), &bad_unit, f64::INFINITY);
+        assert!(matches!(
+            d.reason,
+            MoveReason::Error(EcosafetyError::UnitMismatch { .. })
+        ));
+    }
+}
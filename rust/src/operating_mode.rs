@@ -0,0 +1,403 @@
+use crate::gates::{GateResult, ResidualFlags};
+use crate::types::UnknownVariant;
+use std::fmt;
+use std::str::FromStr;
+
+/// Furnace operating mode (telemetry `mode` column).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum OperatingMode {
+    Startup,
+    Normal,
+    Derate,
+    Shutdown,
+    ScaleUp,
+    Bonus,
+}
+
+impl OperatingMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OperatingMode::Startup => "STARTUP",
+            OperatingMode::Normal => "NORMAL",
+            OperatingMode::Derate => "DERATE",
+            OperatingMode::Shutdown => "SHUTDOWN",
+            OperatingMode::ScaleUp => "SCALE_UP",
+            OperatingMode::Bonus => "BONUS",
+        }
+    }
+
+    /// Gold limits are mandatory in SCALE_UP and BONUS (grammar E.3).
+    pub fn requires_gold(&self) -> bool {
+        matches!(self, OperatingMode::ScaleUp | OperatingMode::Bonus)
+    }
+}
+
+impl fmt::Display for OperatingMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for OperatingMode {
+    type Err = UnknownVariant;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_uppercase().as_str() {
+            "STARTUP" => Ok(OperatingMode::Startup),
+            "NORMAL" | "OPERATE" => Ok(OperatingMode::Normal),
+            "DERATE" => Ok(OperatingMode::Derate),
+            "SHUTDOWN" | "STOP" => Ok(OperatingMode::Shutdown),
+            "SCALE_UP" | "SCALEUP" => Ok(OperatingMode::ScaleUp),
+            "BONUS" => Ok(OperatingMode::Bonus),
+            _ => Err(UnknownVariant {
+                kind: "operating mode",
+                value: s.to_string(),
+            }),
+        }
+    }
+}
+
+/// A recorded mode change and what triggered it.
+#[derive(Clone, Debug)]
+pub struct ModeTransition {
+    pub step: u64,
+    pub from: OperatingMode,
+    pub to: OperatingMode,
+    pub reason: String,
+}
+
+/// Result of one `ModeMachine::step`.
+#[derive(Clone, Debug)]
+pub struct ModeStep {
+    pub mode: OperatingMode,
+    pub transition: Option<ModeTransition>,
+    /// Why an operator request was refused, if it was.
+    pub denied: Option<String>,
+}
+
+/// Explicit furnace mode state machine guarded by `GateResult`.
+///
+/// Forced transitions are checked first, in every mode:
+/// - corridor breach (some r ≥ 1) → SHUTDOWN;
+/// - legal breach → DERATE (except in STARTUP, which simply cannot leave);
+/// - SafetyGate false (V increased) → DERATE;
+/// - SCALE_UP/BONUS without ScaleUpGate (gold or LCA lost), or with a gold
+///   limit exceeded → NORMAL.
+///
+/// Requested transitions are then granted only if the gate for the target
+/// mode holds: NORMAL needs SafetyGate, SCALE_UP/BONUS need ScaleUpGate and
+/// every gold limit met. Gold is checked on the flags directly as well, so a
+/// gate computed from stale flags cannot admit a gold-only mode. BONUS is reachable only from SCALE_UP, and a SHUTDOWN furnace must go
+/// through STARTUP. SHUTDOWN can always be requested.
+#[derive(Clone, Debug)]
+pub struct ModeMachine {
+    mode: OperatingMode,
+    step: u64,
+    history: Vec<ModeTransition>,
+}
+
+impl Default for ModeMachine {
+    fn default() -> Self {
+        ModeMachine::new(OperatingMode::Startup)
+    }
+}
+
+impl ModeMachine {
+    pub fn new(initial: OperatingMode) -> Self {
+        ModeMachine {
+            mode: initial,
+            step: 0,
+            history: Vec::new(),
+        }
+    }
+
+    pub fn mode(&self) -> OperatingMode {
+        self.mode
+    }
+
+    pub fn history(&self) -> &[ModeTransition] {
+        &self.history
+    }
+
+    fn forced(&self, gates: &GateResult, flags: &ResidualFlags) -> Option<(OperatingMode, String)> {
+        use OperatingMode::*;
+        if self.mode == Shutdown {
+            return None;
+        }
+        if !flags.corridor_ok {
+            return Some((Shutdown, "hard corridor limit exceeded".to_string()));
+        }
+        if self.mode == Startup {
+            return None;
+        }
+        if !flags.legal_ok && self.mode != Derate {
+            return Some((Derate, "legal limit exceeded".to_string()));
+        }
+        if !gates.safety_gate && self.mode != Derate {
+            return Some((
+                Derate,
+                "SafetyGate false: Lyapunov residual increased".to_string(),
+            ));
+        }
+        if self.mode.requires_gold() {
+            let reason = match (gates.scaleup_gate, flags.gold_ok) {
+                (true, true) => return None,
+                (true, false) => "gold limit exceeded",
+                (false, true) => "ScaleUpGate false: LCA gate not satisfied",
+                (false, false) => "ScaleUpGate false: gold limit exceeded",
+            };
+            return Some((Normal, reason.to_string()));
+        }
+        None
+    }
+
+    fn admit(
+        &self,
+        to: OperatingMode,
+        gates: &GateResult,
+        flags: &ResidualFlags,
+    ) -> Result<String, String> {
+        use OperatingMode::*;
+        match (self.mode, to) {
+            (_, Shutdown) => Ok("shutdown requested".to_string()),
+            (Shutdown, Startup) => Ok("restart requested".to_string()),
+            (Shutdown, _) => Err("a SHUTDOWN furnace must restart through STARTUP".to_string()),
+            (Startup | Derate | ScaleUp | Bonus, Normal) => {
+                if gates.safety_gate {
+                    Ok("SafetyGate true".to_string())
+                } else {
+                    Err("SafetyGate false".to_string())
+                }
+            }
+            (Normal, ScaleUp) | (ScaleUp, Bonus) => {
+                if !flags.gold_ok {
+                    Err("gold limit exceeded".to_string())
+                } else if gates.scaleup_gate {
+                    Ok("ScaleUpGate true".to_string())
+                } else {
+                    Err("ScaleUpGate false".to_string())
+                }
+            }
+            (Bonus, ScaleUp) => Ok("bonus mode released".to_string()),
+            (_, Derate) => Ok("derate requested".to_string()),
+            (from, to) => Err(format!("no transition from {} to {}", from, to)),
+        }
+    }
+
+    fn record(&mut self, to: OperatingMode, reason: String) -> ModeTransition {
+        let t = ModeTransition {
+            step: self.step,
+            from: self.mode,
+            to,
+            reason,
+        };
+        self.mode = to;
+        self.history.push(t.clone());
+        t
+    }
+
+    /// Advance one control step with the current gates and flags and an
+    /// optional operator request.
+    pub fn step(
+        &mut self,
+        gates: &GateResult,
+        flags: &ResidualFlags,
+        request: Option<OperatingMode>,
+    ) -> ModeStep {
+        self.step += 1;
+
+        if let Some((to, reason)) = self.forced(gates, flags) {
+            let transition = self.record(to, reason);
+            let denied = request
+                .filter(|r| *r != to)
+                .map(|r| format!("request for {} overridden by forced transition", r));
+            return ModeStep {
+                mode: self.mode,
+                transition: Some(transition),
+                denied,
+            };
+        }
+
+        let mut transition = None;
+        let mut denied = None;
+        if let Some(to) = request.filter(|r| *r != self.mode) {
+            match self.admit(to, gates, flags) {
+                Ok(reason) => transition = Some(self.record(to, reason)),
+                Err(reason) => denied = Some(reason),
+            }
+        }
+        ModeStep {
+            mode: self.mode,
+            transition,
+            denied,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gates(safety: bool, scaleup: bool) -> GateResult {
+        GateResult {
+            safety_gate: safety,
+            scaleup_gate: scaleup,
+            deployment_gate: true,
+        }
+    }
+
+    fn flags(corridor: bool, legal: bool, gold: bool) -> ResidualFlags {
+        ResidualFlags {
+            corridor_ok: corridor,
+            legal_ok: legal,
+            gold_ok: gold,
+        }
+    }
+
+    #[test]
+    fn test_scale_up_requires_gold_and_downgrades() {
+        let mut m = ModeMachine::default();
+        m.step(
+            &gates(true, true),
+            &flags(true, true, true),
+            Some(OperatingMode::Normal),
+        );
+        let s = m.step(
+            &gates(true, false),
+            &flags(true, true, false),
+            Some(OperatingMode::ScaleUp),
+        );
+        assert_eq!(s.mode, OperatingMode::Normal);
+        assert!(s.denied.is_some());
+
+        m.step(
+            &gates(true, true),
+            &flags(true, true, true),
+            Some(OperatingMode::ScaleUp),
+        );
+        assert_eq!(m.mode(), OperatingMode::ScaleUp);
+        let s = m.step(&gates(true, false), &flags(true, true, false), None);
+        assert_eq!(s.mode, OperatingMode::Normal);
+        assert_eq!(
+            s.transition.unwrap().reason,
+            "ScaleUpGate false: gold limit exceeded"
+        );
+    }
+
+    #[test]
+    fn test_forced_derate_and_shutdown_are_recorded() {
+        let mut m = ModeMachine::new(OperatingMode::Normal);
+        m.step(&gates(false, false), &flags(true, true, true), None);
+        assert_eq!(m.mode(), OperatingMode::Derate);
+        m.step(
+            &gates(false, false),
+            &flags(false, false, false),
+            Some(OperatingMode::Normal),
+        );
+        assert_eq!(m.mode(), OperatingMode::Shutdown);
+        let s = m.step(
+            &gates(true, true),
+            &flags(true, true, true),
+            Some(OperatingMode::Normal),
+        );
+        assert_eq!(s.mode, OperatingMode::Shutdown);
+
+        let h = m.history();
+        assert_eq!(h.len(), 2);
+        assert_eq!(
+            (h[0].from, h[0].to),
+            (OperatingMode::Normal, OperatingMode::Derate)
+        );
+        assert_eq!(
+            (h[1].from, h[1].to),
+            (OperatingMode::Derate, OperatingMode::Shutdown)
+        );
+    }
+
+    #[test]
+    fn test_gold_is_required_even_with_the_scale_up_gate() {
+        let mut m = ModeMachine::new(OperatingMode::Normal);
+        let s = m.step(
+            &gates(true, true),
+            &flags(true, true, false),
+            Some(OperatingMode::ScaleUp),
+        );
+        assert_eq!(s.mode, OperatingMode::Normal);
+        assert_eq!(s.denied.as_deref(), Some("gold limit exceeded"));
+
+        m.step(
+            &gates(true, true),
+            &flags(true, true, true),
+            Some(OperatingMode::ScaleUp),
+        );
+        assert_eq!(m.mode(), OperatingMode::ScaleUp);
+        let s = m.step(&gates(true, true), &flags(true, true, false), None);
+        assert_eq!(s.mode, OperatingMode::Normal);
+        assert_eq!(s.transition.unwrap().reason, "gold limit exceeded");
+    }
+
+    #[test]
+    fn test_bonus_only_from_scale_up() {
+        let ok = (gates(true, true), flags(true, true, true));
+        let mut m = ModeMachine::new(OperatingMode::Normal);
+        let s = m.step(&ok.0, &ok.1, Some(OperatingMode::Bonus));
+        assert_eq!(s.mode, OperatingMode::Normal);
+        assert_eq!(
+            s.denied.as_deref(),
+            Some("no transition from NORMAL to BONUS")
+        );
+
+        m.step(&ok.0, &ok.1, Some(OperatingMode::ScaleUp));
+        let s = m.step(&gates(true, false), &ok.1, Some(OperatingMode::Bonus));
+        // Losing the gate in SCALE_UP forces NORMAL over the request.
+        assert_eq!(s.mode, OperatingMode::Normal);
+        assert!(s.denied.is_some());
+
+        m.step(&ok.0, &ok.1, Some(OperatingMode::ScaleUp));
+        let s = m.step(&ok.0, &ok.1, Some(OperatingMode::Bonus));
+        assert_eq!(s.mode, OperatingMode::Bonus);
+        assert_eq!(s.transition.unwrap().reason, "ScaleUpGate true");
+        let s = m.step(&ok.0, &ok.1, Some(OperatingMode::ScaleUp));
+        assert_eq!(s.transition.unwrap().reason, "bonus mode released");
+
+        m.step(&ok.0, &ok.1, Some(OperatingMode::Bonus));
+        let s = m.step(&gates(true, false), &flags(true, true, true), None);
+        assert_eq!(s.mode, OperatingMode::Normal);
+        assert_eq!(
+            s.transition.unwrap().reason,
+            "ScaleUpGate false: LCA gate not satisfied"
+        );
+    }
+
+    #[test]
+    fn test_restart_after_shutdown_goes_through_startup() {
+        let ok = (gates(true, true), flags(true, true, true));
+        let mut m = ModeMachine::new(OperatingMode::Normal);
+        m.step(&ok.0, &flags(false, true, true), None);
+        assert_eq!(m.mode(), OperatingMode::Shutdown);
+
+        for to in [OperatingMode::Normal, OperatingMode::ScaleUp] {
+            let s = m.step(&ok.0, &ok.1, Some(to));
+            assert_eq!(s.mode, OperatingMode::Shutdown);
+            assert_eq!(
+                s.denied.as_deref(),
+                Some("a SHUTDOWN furnace must restart through STARTUP")
+            );
+        }
+
+        let s = m.step(&ok.0, &ok.1, Some(OperatingMode::Startup));
+        assert_eq!(s.mode, OperatingMode::Startup);
+        assert_eq!(s.transition.unwrap().reason, "restart requested");
+        // STARTUP cannot leave without SafetyGate, and a breach shuts down again.
+        let s = m.step(&gates(false, false), &ok.1, Some(OperatingMode::Normal));
+        assert_eq!(s.mode, OperatingMode::Startup);
+        assert_eq!(s.denied.as_deref(), Some("SafetyGate false"));
+        let s = m.step(&ok.0, &ok.1, Some(OperatingMode::Normal));
+        assert_eq!(s.mode, OperatingMode::Normal);
+
+        m.step(&ok.0, &flags(false, true, true), None);
+        m.step(&ok.0, &ok.1, Some(OperatingMode::Startup));
+        let s = m.step(&ok.0, &flags(false, true, true), None);
+        assert_eq!(s.mode, OperatingMode::Shutdown);
+    }
+}
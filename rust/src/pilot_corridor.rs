@@ -0,0 +1,590 @@
+use crate::error::EcosafetyError;
+use crate::types::UnknownVariant;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+/// Calendar date of a pilot evidence record (UTC, proleptic Gregorian).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Date {
+    pub year: i32,
+    pub month: u32,
+    pub day: u32,
+}
+
+impl Date {
+    pub fn new(year: i32, month: u32, day: u32) -> Option<Self> {
+        let leap = (year % 4 == 0 && year % 100 != 0) || year % 400 == 0;
+        let days_in_month = match month {
+            1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+            4 | 6 | 9 | 11 => 30,
+            2 if leap => 29,
+            2 => 28,
+            _ => return None,
+        };
+        if day == 0 || day > days_in_month {
+            return None;
+        }
+        Some(Date { year, month, day })
+    }
+
+    /// Days since 1970-01-01.
+    pub fn days_since_epoch(&self) -> i64 {
+        let y = i64::from(self.year) - i64::from(self.month <= 2);
+        let era = y.div_euclid(400);
+        let yoe = y - era * 400;
+        let m = i64::from(self.month);
+        let doy = (153 * (m + if m > 2 { -3 } else { 9 }) + 2) / 5 + i64::from(self.day) - 1;
+        let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+        era * 146_097 + doe - 719_468
+    }
+
+    pub fn days_until(&self, later: &Date) -> i64 {
+        later.days_since_epoch() - self.days_since_epoch()
+    }
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+/// Accepts `YYYY-MM-DD` or an ISO-8601 timestamp such as the shards'
+/// `2026-01-17T00:00:00Z`; the time part is ignored.
+impl FromStr for Date {
+    type Err = UnknownVariant;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || UnknownVariant {
+            kind: "date",
+            value: s.to_string(),
+        };
+        let date = s.trim().split('T').next().unwrap_or("");
+        let mut parts = date.splitn(3, '-');
+        let year = parts.next().and_then(|p| p.parse().ok()).ok_or_else(err)?;
+        let month = parts.next().and_then(|p| p.parse().ok()).ok_or_else(err)?;
+        let day = parts.next().and_then(|p| p.parse().ok()).ok_or_else(err)?;
+        Date::new(year, month, day).ok_or_else(err)
+    }
+}
+
+/// One piece of pilot evidence. Expired evidence no longer counts towards
+/// any predicate; evidence dated after the evaluation date is ignored.
+#[derive(Clone, Debug)]
+pub struct Evidence<T> {
+    pub id: String,
+    pub recorded: Date,
+    pub expires: Option<Date>,
+    pub item: T,
+}
+
+impl<T> Evidence<T> {
+    pub fn is_current(&self, as_of: Date) -> bool {
+        self.recorded <= as_of && self.expires.map_or(true, |e| as_of <= e)
+    }
+}
+
+/// Structural/hydraulic inspection of one element (tray line, stack, basin).
+#[derive(Clone, Debug)]
+pub struct InspectionRecord {
+    pub element: String,
+    pub passed: bool,
+    pub findings: String,
+}
+
+/// Acceptance survey among operators, residents or facility staff.
+#[derive(Clone, Debug)]
+pub struct AcceptanceSurvey {
+    pub population: String,
+    pub respondents: u32,
+    pub accepting: u32,
+}
+
+impl AcceptanceSurvey {
+    /// Rejects a survey without respondents and more accepting answers than
+    /// respondents.
+    pub fn new(population: &str, respondents: u32, accepting: u32) -> Result<Self, EcosafetyError> {
+        if respondents == 0 {
+            return Err(EcosafetyError::InvalidConfig {
+                field: "respondents".to_string(),
+                reason: format!("no respondents for {}", population),
+            });
+        }
+        if accepting > respondents {
+            return Err(EcosafetyError::InvalidConfig {
+                field: "accepting".to_string(),
+                reason: format!(
+                    "{} accepting out of {} respondents for {}",
+                    accepting, respondents, population
+                ),
+            });
+        }
+        Ok(AcceptanceSurvey {
+            population: population.to_string(),
+            respondents,
+            accepting,
+        })
+    }
+}
+
+/// Fouling / operations-and-maintenance log entry.
+#[derive(Clone, Debug)]
+pub struct MaintenanceLog {
+    pub asset: String,
+    pub fouling_index: f64,
+    pub scheduled_work_done: bool,
+}
+
+/// Social-governance indicator (grievance handling, oversight sign-off,
+/// soulsafety exposure band for prisons/MRFs).
+#[derive(Clone, Debug)]
+pub struct GovernanceCheck {
+    pub indicator: String,
+    pub ok: bool,
+    pub note: String,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct PilotEvidence {
+    pub inspections: Vec<Evidence<InspectionRecord>>,
+    pub surveys: Vec<Evidence<AcceptanceSurvey>>,
+    pub maintenance: Vec<Evidence<MaintenanceLog>>,
+    pub governance: Vec<Evidence<GovernanceCheck>>,
+}
+
+/// Thresholds for the Pilot-Gate predicates.
+#[derive(Clone, Debug)]
+pub struct PilotPolicy {
+    pub min_respondents: u32,
+    pub min_acceptance: f64,
+    pub max_fouling_index: f64,
+    /// Maintenance logs must cover at least this many days (one seasonal cycle).
+    pub min_maintenance_span_days: i64,
+    /// Indicators that must each have a current, passing governance check.
+    pub required_indicators: Vec<String>,
+}
+
+impl Default for PilotPolicy {
+    fn default() -> Self {
+        PilotPolicy {
+            min_respondents: 30,
+            min_acceptance: 0.7,
+            max_fouling_index: 1.0,
+            min_maintenance_span_days: 365,
+            required_indicators: Vec::new(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PilotPredicate {
+    HydraulicStructural,
+    TreatmentSat,
+    FoulingOm,
+    SocialGovernance,
+}
+
+impl PilotPredicate {
+    pub const ALL: [PilotPredicate; 4] = [
+        PilotPredicate::HydraulicStructural,
+        PilotPredicate::TreatmentSat,
+        PilotPredicate::FoulingOm,
+        PilotPredicate::SocialGovernance,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PilotPredicate::HydraulicStructural => "hydraulicstructural_ok",
+            PilotPredicate::TreatmentSat => "treatmentsat_ok",
+            PilotPredicate::FoulingOm => "foulingom_ok",
+            PilotPredicate::SocialGovernance => "socialgovernance_ok",
+        }
+    }
+}
+
+impl fmt::Display for PilotPredicate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Outcome of one predicate with the evidence it used and why it failed.
+#[derive(Clone, Debug)]
+pub struct PredicateVerdict {
+    pub predicate: PilotPredicate,
+    pub ok: bool,
+    pub evidence_ids: Vec<String>,
+    pub expired_ids: Vec<String>,
+    pub reasons: Vec<String>,
+}
+
+#[derive(Clone, Debug)]
+pub struct PilotGateVerdict {
+    pub as_of: Date,
+    pub predicates: Vec<PredicateVerdict>,
+    pub pilot_gates_ok: bool,
+}
+
+impl PilotGateVerdict {
+    /// Predicates that block deployment.
+    pub fn failed(&self) -> impl Iterator<Item = &PredicateVerdict> {
+        self.predicates.iter().filter(|p| !p.ok)
+    }
+}
+
+/// Split evidence into current records and expired ids; evidence dated in the
+/// future is dropped.
+fn current<T>(evidence: &[Evidence<T>], as_of: Date) -> (Vec<&Evidence<T>>, Vec<String>) {
+    let mut live = Vec::new();
+    let mut expired = Vec::new();
+    for e in evidence.iter().filter(|e| e.recorded <= as_of) {
+        if e.is_current(as_of) {
+            live.push(e);
+        } else {
+            expired.push(e.id.clone());
+        }
+    }
+    (live, expired)
+}
+
+/// Most recent current record per key.
+fn latest_by<'a, T>(
+    live: &[&'a Evidence<T>],
+    key: impl Fn(&T) -> &str,
+) -> BTreeMap<String, &'a Evidence<T>> {
+    let mut latest: BTreeMap<String, &Evidence<T>> = BTreeMap::new();
+    for e in live {
+        let slot = latest.entry(key(&e.item).to_string()).or_insert(e);
+        if e.recorded >= slot.recorded {
+            *slot = e;
+        }
+    }
+    latest
+}
+
+fn verdict<T>(
+    predicate: PilotPredicate,
+    used: &[&Evidence<T>],
+    expired_ids: Vec<String>,
+    reasons: Vec<String>,
+) -> PredicateVerdict {
+    PredicateVerdict {
+        predicate,
+        ok: reasons.is_empty(),
+        evidence_ids: used.iter().map(|e| e.id.clone()).collect(),
+        expired_ids,
+        reasons,
+    }
+}
+
+/// Latest current inspection of every element must have passed.
+pub fn hydraulicstructural_ok(
+    inspections: &[Evidence<InspectionRecord>],
+    as_of: Date,
+) -> PredicateVerdict {
+    let (live, expired) = current(inspections, as_of);
+    let latest = latest_by(&live, |i| &i.element);
+    let mut reasons = Vec::new();
+    if latest.is_empty() {
+        reasons.push("no current inspection record".to_string());
+    }
+    for (element, e) in &latest {
+        if !e.item.passed {
+            reasons.push(format!(
+                "inspection {} of {} failed: {}",
+                e.id, element, e.item.findings
+            ));
+        }
+    }
+    let used: Vec<_> = latest.into_values().collect();
+    verdict(PilotPredicate::HydraulicStructural, &used, expired, reasons)
+}
+
+/// Latest current survey of every population must reach the respondent
+/// count and acceptance share of the policy. A survey without respondents,
+/// or with more accepting answers than respondents, fails.
+pub fn treatmentsat_ok(
+    surveys: &[Evidence<AcceptanceSurvey>],
+    policy: &PilotPolicy,
+    as_of: Date,
+) -> PredicateVerdict {
+    let (live, expired) = current(surveys, as_of);
+    let latest = latest_by(&live, |s| &s.population);
+    let mut reasons = Vec::new();
+    if latest.is_empty() {
+        reasons.push("no current acceptance survey".to_string());
+    }
+    for (population, e) in &latest {
+        let s = &e.item;
+        if s.respondents == 0 {
+            reasons.push(format!(
+                "survey {} of {} has no respondents",
+                e.id, population
+            ));
+            continue;
+        }
+        if s.respondents < policy.min_respondents {
+            reasons.push(format!(
+                "survey {} of {} has {} respondents (< {})",
+                e.id, population, s.respondents, policy.min_respondents
+            ));
+            continue;
+        }
+        if s.accepting > s.respondents {
+            reasons.push(format!(
+                "survey {} of {}: {} accepting out of {} respondents",
+                e.id, population, s.accepting, s.respondents
+            ));
+            continue;
+        }
+        let share = f64::from(s.accepting) / f64::from(s.respondents);
+        if share < policy.min_acceptance {
+            reasons.push(format!(
+                "survey {} of {}: acceptance {:.2} < {:.2}",
+                e.id, population, share, policy.min_acceptance
+            ));
+        }
+    }
+    let used: Vec<_> = latest.into_values().collect();
+    verdict(PilotPredicate::TreatmentSat, &used, expired, reasons)
+}
+
+/// The latest current log of every asset must show scheduled work done and
+/// fouling within the policy limit, and the current logs together must span
+/// a full seasonal cycle.
+pub fn foulingom_ok(
+    logs: &[Evidence<MaintenanceLog>],
+    policy: &PilotPolicy,
+    as_of: Date,
+) -> PredicateVerdict {
+    let (live, expired) = current(logs, as_of);
+    let mut reasons = Vec::new();
+    match (
+        live.iter().map(|e| e.recorded).min(),
+        live.iter().map(|e| e.recorded).max(),
+    ) {
+        (Some(first), Some(last)) => {
+            let span = first.days_until(&last);
+            if span < policy.min_maintenance_span_days {
+                reasons.push(format!(
+                    "maintenance logs span {} days ({} to {}), need {}",
+                    span, first, last, policy.min_maintenance_span_days
+                ));
+            }
+        }
+        _ => reasons.push("no current maintenance log".to_string()),
+    }
+    for e in latest_by(&live, |l| &l.asset).values() {
+        if !e.item.scheduled_work_done {
+            reasons.push(format!(
+                "log {} ({}): scheduled maintenance not done",
+                e.id, e.item.asset
+            ));
+        }
+        if e.item.fouling_index.is_nan() || e.item.fouling_index > policy.max_fouling_index {
+            reasons.push(format!(
+                "log {} ({}): fouling index {} > {}",
+                e.id, e.item.asset, e.item.fouling_index, policy.max_fouling_index
+            ));
+        }
+    }
+    verdict(PilotPredicate::FoulingOm, &live, expired, reasons)
+}
+
+/// Latest current check of every indicator must pass, and every required
+/// indicator must have one.
+pub fn socialgovernance_ok(
+    checks: &[Evidence<GovernanceCheck>],
+    policy: &PilotPolicy,
+    as_of: Date,
+) -> PredicateVerdict {
+    let (live, expired) = current(checks, as_of);
+    let latest = latest_by(&live, |c| &c.indicator);
+    let mut reasons = Vec::new();
+    if latest.is_empty() {
+        reasons.push("no current governance check".to_string());
+    }
+    for indicator in &policy.required_indicators {
+        if !latest.contains_key(indicator) {
+            reasons.push(format!("no current check for `{}`", indicator));
+        }
+    }
+    for (indicator, e) in &latest {
+        if !e.item.ok {
+            reasons.push(format!(
+                "check {} of `{}` failed: {}",
+                e.id, indicator, e.item.note
+            ));
+        }
+    }
+    let used: Vec<_> = latest.into_values().collect();
+    verdict(PilotPredicate::SocialGovernance, &used, expired, reasons)
+}
+
+/// Evaluate all four Pilot-Gate predicates at `as_of`. The result's
+/// `pilot_gates_ok` is the input expected by `gates::compute_gates`.
+pub fn evaluate_pilot_gates(
+    evidence: &PilotEvidence,
+    policy: &PilotPolicy,
+    as_of: Date,
+) -> PilotGateVerdict {
+    let predicates = vec![
+        hydraulicstructural_ok(&evidence.inspections, as_of),
+        treatmentsat_ok(&evidence.surveys, policy, as_of),
+        foulingom_ok(&evidence.maintenance, policy, as_of),
+        socialgovernance_ok(&evidence.governance, policy, as_of),
+    ];
+    let pilot_gates_ok = predicates.iter().all(|p| p.ok);
+    PilotGateVerdict {
+        as_of,
+        predicates,
+        pilot_gates_ok,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn d(s: &str) -> Date {
+        s.parse().unwrap()
+    }
+
+    fn ev<T>(id: &str, recorded: &str, expires: Option<&str>, item: T) -> Evidence<T> {
+        Evidence {
+            id: id.to_string(),
+            recorded: d(recorded),
+            expires: expires.map(d),
+            item,
+        }
+    }
+
+    fn passing() -> PilotEvidence {
+        PilotEvidence {
+            inspections: vec![ev(
+                "INS-1",
+                "2026-01-10",
+                Some("2026-07-10"),
+                InspectionRecord {
+                    element: "tray_line".to_string(),
+                    passed: true,
+                    findings: String::new(),
+                },
+            )],
+            surveys: vec![ev(
+                "SRV-1",
+                "2025-12-01",
+                None,
+                AcceptanceSurvey {
+                    population: "facility_staff".to_string(),
+                    respondents: 40,
+                    accepting: 34,
+                },
+            )],
+            maintenance: vec![
+                ev(
+                    "MNT-1",
+                    "2025-01-05",
+                    None,
+                    MaintenanceLog {
+                        asset: "boiler".to_string(),
+                        fouling_index: 0.4,
+                        scheduled_work_done: true,
+                    },
+                ),
+                ev(
+                    "MNT-2",
+                    "2026-01-12",
+                    None,
+                    MaintenanceLog {
+                        asset: "boiler".to_string(),
+                        fouling_index: 0.6,
+                        scheduled_work_done: true,
+                    },
+                ),
+            ],
+            governance: vec![ev(
+                "GOV-1",
+                "2026-01-01",
+                None,
+                GovernanceCheck {
+                    indicator: "grievance_channel".to_string(),
+                    ok: true,
+                    note: String::new(),
+                },
+            )],
+        }
+    }
+
+    #[test]
+    fn test_all_predicates_pass() {
+        let v = evaluate_pilot_gates(&passing(), &PilotPolicy::default(), d("2026-01-17"));
+        assert!(v.pilot_gates_ok, "{:?}", v.failed().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_expired_evidence_fails_its_predicate_only() {
+        let v = evaluate_pilot_gates(&passing(), &PilotPolicy::default(), d("2026-08-01"));
+        assert!(!v.pilot_gates_ok);
+        let failed: Vec<_> = v.failed().map(|p| p.predicate).collect();
+        assert_eq!(failed, vec![PilotPredicate::HydraulicStructural]);
+        assert_eq!(v.predicates[0].expired_ids, vec!["INS-1".to_string()]);
+    }
+
+    #[test]
+    fn test_only_latest_log_per_asset_counts_for_fouling() {
+        let policy = PilotPolicy::default();
+        let mut evidence = passing();
+        // A fouled boiler log, since cleaned.
+        evidence.maintenance[0].item.fouling_index = 1.8;
+        evidence.maintenance[0].item.scheduled_work_done = false;
+        let v = foulingom_ok(&evidence.maintenance, &policy, d("2026-01-17"));
+        assert!(v.ok, "{:?}", v.reasons);
+
+        // The latest log of a second asset still blocks.
+        evidence.maintenance.push(ev(
+            "MNT-3",
+            "2026-01-14",
+            None,
+            MaintenanceLog {
+                asset: "stack".to_string(),
+                fouling_index: 1.2,
+                scheduled_work_done: true,
+            },
+        ));
+        let v = foulingom_ok(&evidence.maintenance, &policy, d("2026-01-17"));
+        assert!(!v.ok);
+        assert_eq!(v.reasons.len(), 1);
+        assert!(v.reasons[0].contains("MNT-3"));
+    }
+
+    #[test]
+    fn test_more_accepting_than_respondents_is_rejected() {
+        assert!(AcceptanceSurvey::new("residents", 40, 41).is_err());
+        let ok = AcceptanceSurvey::new("residents", 40, 40).unwrap();
+        assert_eq!(ok.accepting, 40);
+
+        let mut evidence = passing();
+        evidence.surveys[0].item.accepting = 41;
+        let v = treatmentsat_ok(&evidence.surveys, &PilotPolicy::default(), d("2026-01-17"));
+        assert!(!v.ok);
+    }
+
+    #[test]
+    fn test_survey_without_respondents_is_rejected() {
+        assert!(AcceptanceSurvey::new("residents", 0, 0).is_err());
+
+        // Even a policy without a respondent minimum must not pass 0/0.
+        let policy = PilotPolicy {
+            min_respondents: 0,
+            ..PilotPolicy::default()
+        };
+        let mut evidence = passing();
+        evidence.surveys[0].item.respondents = 0;
+        evidence.surveys[0].item.accepting = 0;
+        let v = treatmentsat_ok(&evidence.surveys, &policy, d("2026-01-17"));
+        assert!(!v.ok);
+        assert!(v.reasons.iter().any(|r| r.contains("no respondents")));
+    }
+}
@@ -0,0 +1,309 @@
+use crate::error::EcosafetyError;
+use crate::lyapunov::{ResidualState, RiskCoord};
+use crate::types::UnknownVariant;
+use std::fmt;
+use std::str::FromStr;
+
+/// Shape of the Lyapunov residual V(r). Every form is 0 at r = 0 and
+/// non-decreasing in each r_j, so `is_admissible` and the gates keep their
+/// meaning whichever form a corridor declares.
+pub trait ResidualForm {
+    /// V over `coords`, given in `CorridorSet::iter()` order.
+    fn residual(&self, coords: &[RiskCoord]) -> Result<f64, EcosafetyError>;
+
+    /// Whether V is still meaningful over a subset of the coordinates (the
+    /// unmeasured ones dropped). False for forms that couple coordinates.
+    fn separable(&self) -> bool {
+        true
+    }
+}
+
+/// V = Σ w·r (grammar §2.1).
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Linear;
+
+/// V = max w·r: a single channel near its limit dominates.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct MaxNorm;
+
+/// V = Σ −w·ln(1 − r): grows without bound as any weighted r → 1.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct LogBarrier;
+
+/// V = rᵀPr with P symmetric positive semidefinite. Without an explicit P the
+/// form is P = diag(w), i.e. V = Σ w·r².
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Quadratic {
+    p: Option<Vec<Vec<f64>>>,
+}
+
+impl Quadratic {
+    pub fn weighted() -> Self {
+        Quadratic { p: None }
+    }
+
+    /// Explicit P, rows and columns in `CorridorSet::iter()` order. Rejects
+    /// non-square, non-symmetric, non-finite or indefinite matrices.
+    pub fn new(p: Vec<Vec<f64>>) -> Result<Self, EcosafetyError> {
+        let invalid = |reason: String| EcosafetyError::InvalidConfig {
+            field: "P".to_string(),
+            reason,
+        };
+        let n = p.len();
+        if n == 0 || p.iter().any(|row| row.len() != n) {
+            return Err(invalid("P must be a non-empty square matrix".to_string()));
+        }
+        if p.iter().flatten().any(|x| !x.is_finite()) {
+            return Err(invalid("P must be finite".to_string()));
+        }
+        let scale = p.iter().flatten().fold(0.0f64, |m, x| m.max(x.abs()));
+        let tol = 1e-12 * scale.max(1.0);
+        for (i, row) in p.iter().enumerate() {
+            for (j, pij) in row.iter().enumerate().take(i) {
+                if (pij - p[j][i]).abs() > tol {
+                    return Err(invalid(format!("P is not symmetric at ({}, {})", i, j)));
+                }
+            }
+        }
+        if !positive_semidefinite(&p, tol) {
+            return Err(invalid("P is not positive semidefinite".to_string()));
+        }
+        Ok(Quadratic { p: Some(p) })
+    }
+
+    pub fn matrix(&self) -> Option<&[Vec<f64>]> {
+        self.p.as_deref()
+    }
+
+    /// The principal submatrix on `indices`, which is PSD whenever P is.
+    pub fn restrict(&self, indices: &[usize]) -> Quadratic {
+        Quadratic {
+            p: self.p.as_ref().map(|p| {
+                indices
+                    .iter()
+                    .map(|&i| indices.iter().map(|&j| p[i][j]).collect())
+                    .collect()
+            }),
+        }
+    }
+}
+
+/// Cholesky factorisation that tolerates zero pivots: for a PSD matrix a
+/// (numerically) zero pivot forces the rest of its column to zero as well.
+fn positive_semidefinite(p: &[Vec<f64>], tol: f64) -> bool {
+    let n = p.len();
+    let mut l = vec![vec![0.0; n]; n];
+    for j in 0..n {
+        let d = p[j][j] - (0..j).map(|k| l[j][k] * l[j][k]).sum::<f64>();
+        if d < -tol {
+            return false;
+        }
+        let pivot = if d > tol { d.sqrt() } else { 0.0 };
+        l[j][j] = pivot;
+        for i in (j + 1)..n {
+            let s = p[i][j] - (0..j).map(|k| l[i][k] * l[j][k]).sum::<f64>();
+            if pivot == 0.0 {
+                if s.abs() > tol.sqrt() {
+                    return false;
+                }
+            } else {
+                l[i][j] = s / pivot;
+            }
+        }
+    }
+    true
+}
+
+impl ResidualForm for Linear {
+    fn residual(&self, coords: &[RiskCoord]) -> Result<f64, EcosafetyError> {
+        Ok(coords.iter().map(|c| c.w * c.r).sum())
+    }
+}
+
+impl ResidualForm for MaxNorm {
+    fn residual(&self, coords: &[RiskCoord]) -> Result<f64, EcosafetyError> {
+        Ok(coords.iter().map(|c| c.w * c.r).fold(0.0, f64::max))
+    }
+}
+
+impl ResidualForm for LogBarrier {
+    fn residual(&self, coords: &[RiskCoord]) -> Result<f64, EcosafetyError> {
+        Ok(coords
+            .iter()
+            .filter(|c| c.w > 0.0)
+            .map(|c| {
+                if c.r >= 1.0 {
+                    f64::INFINITY
+                } else {
+                    -c.w * (-c.r).ln_1p()
+                }
+            })
+            .sum())
+    }
+}
+
+impl ResidualForm for Quadratic {
+    fn residual(&self, coords: &[RiskCoord]) -> Result<f64, EcosafetyError> {
+        let p = match &self.p {
+            None => return Ok(coords.iter().map(|c| c.w * c.r * c.r).sum()),
+            Some(p) => p,
+        };
+        if p.len() != coords.len() {
+            return Err(EcosafetyError::InvalidConfig {
+                field: "P".to_string(),
+                reason: format!(
+                    "P is {}x{} but there are {} risk coordinates",
+                    p.len(),
+                    p.len(),
+                    coords.len()
+                ),
+            });
+        }
+        let mut v = 0.0;
+        for (i, row) in p.iter().enumerate() {
+            for (j, pij) in row.iter().enumerate() {
+                v += coords[i].r * pij * coords[j].r;
+            }
+        }
+        // rᵀPr ≥ 0 for PSD P; clamp rounding noise.
+        Ok(v.max(0.0))
+    }
+
+    fn separable(&self) -> bool {
+        self.p.is_none()
+    }
+}
+
+/// Residual form declared by a corridor spec (`residual_form` column).
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum CorridorForm {
+    #[default]
+    Linear,
+    Quadratic(Quadratic),
+    MaxNorm,
+    LogBarrier,
+}
+
+impl CorridorForm {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CorridorForm::Linear => "LINEAR",
+            CorridorForm::Quadratic(_) => "QUADRATIC",
+            CorridorForm::MaxNorm => "MAX_NORM",
+            CorridorForm::LogBarrier => "LOG_BARRIER",
+        }
+    }
+
+    /// The form over the coordinates at `indices` (positions in
+    /// `CorridorSet::iter()` order), for sub-residuals of a corridor.
+    pub fn restrict(&self, indices: &[usize]) -> CorridorForm {
+        match self {
+            CorridorForm::Quadratic(q) => CorridorForm::Quadratic(q.restrict(indices)),
+            other => other.clone(),
+        }
+    }
+
+    fn as_form(&self) -> &dyn ResidualForm {
+        match self {
+            CorridorForm::Linear => &Linear,
+            CorridorForm::Quadratic(q) => q,
+            CorridorForm::MaxNorm => &MaxNorm,
+            CorridorForm::LogBarrier => &LogBarrier,
+        }
+    }
+}
+
+impl ResidualForm for CorridorForm {
+    fn residual(&self, coords: &[RiskCoord]) -> Result<f64, EcosafetyError> {
+        self.as_form().residual(coords)
+    }
+
+    fn separable(&self) -> bool {
+        self.as_form().separable()
+    }
+}
+
+impl fmt::Display for CorridorForm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// `QUADRATIC` parses to the weighted form P = diag(w); an explicit P is set
+/// through `CorridorSet::with_form`.
+impl FromStr for CorridorForm {
+    type Err = UnknownVariant;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_uppercase().as_str() {
+            "LINEAR" | "WEIGHTED_SUM" => Ok(CorridorForm::Linear),
+            "QUADRATIC" => Ok(CorridorForm::Quadratic(Quadratic::weighted())),
+            "MAX" | "MAX_NORM" => Ok(CorridorForm::MaxNorm),
+            "LOG_BARRIER" | "BARRIER" => Ok(CorridorForm::LogBarrier),
+            _ => Err(UnknownVariant {
+                kind: "residual form",
+                value: s.to_string(),
+            }),
+        }
+    }
+}
+
+/// Like `lyapunov::compute_residual`, with V computed by `form`.
+pub fn compute_residual_with<F: ResidualForm + ?Sized>(
+    form: &F,
+    coords: &[RiskCoord],
+) -> Result<ResidualState, EcosafetyError> {
+    if coords.is_empty() {
+        return Err(EcosafetyError::NoCorridor);
+    }
+    let v = form.residual(coords)?;
+    if v.is_nan() {
+        return Err(EcosafetyError::InvalidConfig {
+            field: "residual".to_string(),
+            reason: "residual form produced NaN".to_string(),
+        });
+    }
+    Ok(ResidualState {
+        coords: coords.to_vec(),
+        v,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn coords(rs: &[f64]) -> Vec<RiskCoord> {
+        rs.iter().map(|&r| RiskCoord { r, w: 0.5 }).collect()
+    }
+
+    #[test]
+    fn test_forms_agree_at_zero_and_order_near_limit() {
+        let zero = coords(&[0.0, 0.0]);
+        let near = coords(&[0.2, 0.99]);
+        let forms = [
+            CorridorForm::Linear,
+            CorridorForm::Quadratic(Quadratic::weighted()),
+            CorridorForm::MaxNorm,
+            CorridorForm::LogBarrier,
+        ];
+        for f in &forms {
+            assert_eq!(f.residual(&zero).unwrap(), 0.0, "{}", f);
+        }
+        let linear = Linear.residual(&near).unwrap();
+        assert!(LogBarrier.residual(&near).unwrap() > linear);
+        assert_eq!(LogBarrier.residual(&coords(&[1.0])).unwrap(), f64::INFINITY);
+        assert!((MaxNorm.residual(&near).unwrap() - 0.495).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_quadratic_requires_psd_matrix() {
+        assert!(Quadratic::new(vec![vec![1.0, 2.0], vec![2.0, 1.0]]).is_err());
+        assert!(Quadratic::new(vec![vec![1.0, 0.5], vec![0.0, 1.0]]).is_err());
+        let q = Quadratic::new(vec![vec![1.0, 1.0], vec![1.0, 1.0]]).unwrap();
+        let v = q.residual(&coords(&[0.5, 0.5])).unwrap();
+        assert!((v - 1.0).abs() < 1e-12);
+        assert!(!q.separable());
+        assert!(q.residual(&coords(&[0.5])).is_err());
+    }
+}
@@ -0,0 +1,219 @@
+use crate::corridor_shard::{CorridorEntry, CorridorSet};
+use crate::error::EcosafetyError;
+use crate::lyapunov::RiskCoord;
+use crate::residual_form::{compute_residual_with, ResidualForm};
+use std::collections::BTreeSet;
+use std::fmt;
+
+/// A slice of the corridor whose residual is tracked on its own: one
+/// Lyapunov channel, or every parameter sharing a KER role (health, process,
+/// safety in the particles schema).
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ResidualGroup {
+    Channel(u32),
+    Role(String),
+}
+
+impl ResidualGroup {
+    /// Role group; role names compare case-insensitively.
+    pub fn role(name: &str) -> Self {
+        ResidualGroup::Role(name.trim().to_ascii_lowercase())
+    }
+
+    pub fn contains(&self, entry: &CorridorEntry) -> bool {
+        match self {
+            ResidualGroup::Channel(c) => entry.risk.channel == *c,
+            ResidualGroup::Role(role) => entry
+                .ker_role
+                .as_deref()
+                .is_some_and(|r| r.trim().eq_ignore_ascii_case(role)),
+        }
+    }
+}
+
+impl fmt::Display for ResidualGroup {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ResidualGroup::Channel(c) => write!(f, "channel {}", c),
+            ResidualGroup::Role(r) => write!(f, "role {}", r),
+        }
+    }
+}
+
+/// Residual of one group, in the corridor's declared form. `v` is `None`
+/// when none of the group was measured, or when part of it is missing and
+/// the form couples coordinates.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SubResidual {
+    pub group: ResidualGroup,
+    pub v: Option<f64>,
+}
+
+/// Per-group admissibility: V_g,next ≤ V_g,prev + ε, checked on top of the
+/// total V so that one group cannot pay for another.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GroupLimit {
+    pub group: ResidualGroup,
+    pub eps: f64,
+}
+
+impl GroupLimit {
+    /// The group residual may not increase at all, e.g. `role("health")`.
+    pub fn never_increase(group: ResidualGroup) -> Self {
+        GroupLimit { group, eps: 0.0 }
+    }
+}
+
+/// Every channel of the corridor, then every KER role, without duplicates.
+pub fn corridor_groups(corridors: &CorridorSet) -> Vec<ResidualGroup> {
+    let channels: BTreeSet<u32> = corridors.iter().map(|e| e.risk.channel).collect();
+    let roles: BTreeSet<ResidualGroup> = corridors
+        .iter()
+        .filter_map(|e| e.ker_role.as_deref())
+        .map(ResidualGroup::role)
+        .collect();
+    channels
+        .into_iter()
+        .map(ResidualGroup::Channel)
+        .chain(roles)
+        .collect()
+}
+
+/// Sub-residuals for every group of `corridors`. `r` holds one entry per
+/// corridor parameter in `CorridorSet::iter()` order, `None` if unmeasured.
+pub fn sub_residuals(
+    corridors: &CorridorSet,
+    r: &[Option<f64>],
+) -> Result<Vec<SubResidual>, EcosafetyError> {
+    if r.len() != corridors.len() {
+        return Err(EcosafetyError::InvalidConfig {
+            field: "r".to_string(),
+            reason: format!(
+                "{} risk coordinates for {} corridor parameters",
+                r.len(),
+                corridors.len()
+            ),
+        });
+    }
+    let form = corridors.form();
+    let mut out = Vec::new();
+    for group in corridor_groups(corridors) {
+        let mut indices = Vec::new();
+        let mut coords = Vec::new();
+        for (i, (entry, r)) in corridors.iter().zip(r).enumerate() {
+            if !group.contains(entry) {
+                continue;
+            }
+            indices.push(i);
+            if let Some(r) = r {
+                coords.push(RiskCoord {
+                    r: *r,
+                    w: entry.risk.weight_w,
+                });
+            }
+        }
+        let v = if coords.is_empty() || (coords.len() < indices.len() && !form.separable()) {
+            None
+        } else {
+            Some(compute_residual_with(&form.restrict(&indices), &coords)?.v)
+        };
+        out.push(SubResidual { group, v });
+    }
+    Ok(out)
+}
+
+/// The residual recorded for `group`, if any.
+pub fn group_residual(groups: &[SubResidual], group: &ResidualGroup) -> Option<f64> {
+    groups.iter().find(|s| &s.group == group).and_then(|s| s.v)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::corridor_shard::parse_corridor_shard;
+    use crate::dual_threshold::Measurement;
+    use crate::lyapunov_controller::{LyapunovController, MoveReason, PlantModel};
+
+    const PARTICLES: &str =
+        include_str!("../../qpudatashards/particles/CybocinderPhoenixCorridors2026v1.csv");
+
+    struct Echo;
+
+    impl PlantModel for Echo {
+        type State = ();
+        type Move = Vec<Measurement>;
+
+        fn predict(
+            &self,
+            _: &(),
+            mv: &Vec<Measurement>,
+        ) -> Result<Vec<Measurement>, EcosafetyError> {
+            Ok(mv.clone())
+        }
+    }
+
+    fn readings(corridors: &CorridorSet, dioxins: f64, co: f64) -> Vec<Measurement> {
+        corridors
+            .iter()
+            .map(|e| {
+                let p = &e.parameter;
+                let value = match p.name.as_str() {
+                    "Dioxins" => dioxins,
+                    "CO" => co,
+                    // Comfortably inside the corridor on the safe side.
+                    _ => (e.risk.r_min + e.risk.r_max) / 2.0,
+                };
+                Measurement {
+                    param_name: p.name.clone(),
+                    channel: e.risk.channel,
+                    value,
+                    unit: p.unit.clone(),
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_health_limit_blocks_trading_dioxins_for_co() {
+        let corridors = parse_corridor_shard(PARTICLES.as_bytes()).unwrap();
+        let groups = corridor_groups(&corridors);
+        assert!(groups.contains(&ResidualGroup::role("health")));
+        assert!(groups.contains(&ResidualGroup::Channel(8)));
+
+        let health = ResidualGroup::role("Health");
+        let now = crate::dual_threshold::evaluate_thresholds(
+            &corridors,
+            &readings(&corridors, 0.02, 40.0),
+        )
+        .unwrap();
+        // Dioxins up, CO down by more: the total residual falls.
+        let mv = readings(&corridors, 0.03, 10.0);
+
+        let plain = LyapunovController::new(corridors.clone(), Echo, 0.0);
+        let d = plain.is_admissible_move_grouped(
+            &(),
+            &mv,
+            now.residual.as_ref().unwrap().v,
+            &now.groups,
+        );
+        assert!(d.admissible, "{:?}", d.reason);
+        let next_health = group_residual(&d.groups, &health).unwrap();
+        assert!(next_health > group_residual(&now.groups, &health).unwrap());
+
+        let guarded = LyapunovController::new(corridors, Echo, 0.0)
+            .with_group_limit(GroupLimit::never_increase(health.clone()));
+        let d = guarded.is_admissible_move_grouped(
+            &(),
+            &mv,
+            now.residual.as_ref().unwrap().v,
+            &now.groups,
+        );
+        assert!(matches!(
+            d.reason,
+            MoveReason::GroupResidualIncrease { ref group, .. } if *group == health
+        ));
+        // Without a baseline for the limited group the move is refused.
+        let d = guarded.is_admissible_move(&(), &mv, 1e9);
+        assert_eq!(d.reason, MoveReason::MissingGroupBaseline { group: health });
+    }
+}
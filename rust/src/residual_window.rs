@@ -0,0 +1,373 @@
+use crate::corridor_shard::CorridorSet;
+use crate::error::EcosafetyError;
+use crate::lca_uncertainty::percentile;
+use crate::lyapunov::{compute_risk_coord_measured, is_admissible, RiskCoord};
+use crate::pilot_corridor::Date;
+use crate::residual_form::compute_residual_with;
+use crate::telemetry_shard::TelemetryRecord;
+use crate::types::UnknownVariant;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+/// Length of one aggregation window. Windows are aligned to UTC midnight.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WindowSize {
+    HalfHour,
+    Hourly,
+    Daily,
+}
+
+impl WindowSize {
+    pub fn seconds(&self) -> u32 {
+        match self {
+            WindowSize::HalfHour => 1_800,
+            WindowSize::Hourly => 3_600,
+            WindowSize::Daily => 86_400,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WindowSize::HalfHour => "half-hour",
+            WindowSize::Hourly => "hourly",
+            WindowSize::Daily => "daily",
+        }
+    }
+}
+
+impl fmt::Display for WindowSize {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for WindowSize {
+    type Err = UnknownVariant;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "half-hour" | "halfhour" | "30min" | "30m" => Ok(WindowSize::HalfHour),
+            "hourly" | "hour" | "1h" => Ok(WindowSize::Hourly),
+            "daily" | "day" | "1d" => Ok(WindowSize::Daily),
+            _ => Err(UnknownVariant {
+                kind: "window size",
+                value: s.to_string(),
+            }),
+        }
+    }
+}
+
+/// How the samples of one risk coordinate within a window are reduced. The
+/// reduction is applied to r rather than to the raw readings, so `Max` is the
+/// worst case for MAX and MIN parameters alike.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Aggregation {
+    Mean,
+    /// Linear-interpolated percentile, 0..=100.
+    Percentile(f64),
+    Max,
+}
+
+impl Aggregation {
+    fn reduce(&self, samples: &mut [f64]) -> f64 {
+        match *self {
+            Aggregation::Mean => samples.iter().sum::<f64>() / samples.len() as f64,
+            Aggregation::Percentile(p) => {
+                samples.sort_by(f64::total_cmp);
+                percentile(samples, p)
+            }
+            Aggregation::Max => samples.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct WindowConfig {
+    pub size: WindowSize,
+    pub aggregation: Aggregation,
+    /// ε of the admissibility contract between consecutive windows.
+    pub eps: f64,
+    /// V of the window preceding the data, if known. Without it the first
+    /// window only serves as the baseline for the second.
+    pub v_baseline: Option<f64>,
+}
+
+impl Default for WindowConfig {
+    /// The tray pilot rule: daily means, V_{t+1} − V_t ≤ 0.
+    fn default() -> Self {
+        WindowConfig {
+            size: WindowSize::Daily,
+            aggregation: Aggregation::Mean,
+            eps: 0.0,
+            v_baseline: None,
+        }
+    }
+}
+
+/// One aggregation window. `r` follows `CorridorSet::iter()` order; `v` is
+/// `None` unless every corridor parameter has at least one sample.
+#[derive(Clone, Debug)]
+pub struct WindowResidual {
+    pub date: Date,
+    /// Window start, seconds after midnight UTC.
+    pub start: u32,
+    pub samples: usize,
+    pub r: Vec<Option<f64>>,
+    pub v: Option<f64>,
+}
+
+/// Verdict for one production day.
+#[derive(Clone, Debug)]
+pub struct DayVerdict {
+    pub date: Date,
+    pub accepted: bool,
+    pub windows: usize,
+    pub reasons: Vec<String>,
+}
+
+/// Split an ISO-8601 timestamp (`2026-03-01T14:30:00Z`, `2026-03-01 14:30`)
+/// into its date and seconds after midnight. Only UTC is accepted, so that
+/// windows line up across nodes.
+fn parse_timestamp(ts: &str, need_time: bool) -> Result<(Date, u32), EcosafetyError> {
+    let invalid = |reason: &str| EcosafetyError::InvalidConfig {
+        field: "timestamp".to_string(),
+        reason: format!("{}: {}", ts, reason),
+    };
+    let ts = ts.trim();
+    let date: Date = ts
+        .get(..10)
+        .and_then(|d| d.parse().ok())
+        .ok_or_else(|| invalid("expected YYYY-MM-DD"))?;
+    let rest = &ts[10..];
+    if rest.is_empty() {
+        if need_time {
+            return Err(invalid("a time of day is required below daily windows"));
+        }
+        return Ok((date, 0));
+    }
+    let time = rest
+        .strip_prefix('T')
+        .or_else(|| rest.strip_prefix(' '))
+        .ok_or_else(|| invalid("expected T or a space after the date"))?;
+    let time = time
+        .strip_suffix('Z')
+        .or_else(|| time.strip_suffix("+00:00"))
+        .unwrap_or(time);
+    if time.contains(['+', '-']) {
+        return Err(invalid("only UTC timestamps are supported"));
+    }
+    let mut parts = time.splitn(3, ':');
+    let mut field = |max: u32| -> Option<u32> {
+        match parts.next() {
+            None => Some(0),
+            Some(p) => {
+                let whole = p.split('.').next()?;
+                whole.parse().ok().filter(|v| *v < max)
+            }
+        }
+    };
+    let h = field(24).ok_or_else(|| invalid("bad hour"))?;
+    let m = field(60).ok_or_else(|| invalid("bad minute"))?;
+    let s = field(61).ok_or_else(|| invalid("bad second"))?;
+    Ok((date, (h * 3_600 + m * 60 + s).min(86_399)))
+}
+
+/// Aggregate telemetry into windows and compute the windowed V of each, in
+/// the corridor's residual form. Records for parameters outside the corridor
+/// are ignored; windows appear in chronological order, and only windows with
+/// at least one sample are returned.
+pub fn windowed_residuals(
+    corridors: &CorridorSet,
+    records: &[TelemetryRecord],
+    cfg: &WindowConfig,
+) -> Result<Vec<WindowResidual>, EcosafetyError> {
+    if corridors.is_empty() {
+        return Err(EcosafetyError::NoCorridor);
+    }
+    if let Aggregation::Percentile(p) = cfg.aggregation {
+        if !(0.0..=100.0).contains(&p) {
+            return Err(EcosafetyError::InvalidConfig {
+                field: "percentile".to_string(),
+                reason: format!("{} is outside 0..=100", p),
+            });
+        }
+    }
+    let entries: Vec<_> = corridors.iter().collect();
+    let width = cfg.size.seconds();
+    let need_time = cfg.size != WindowSize::Daily;
+
+    let mut buckets: BTreeMap<(Date, u32), Vec<Vec<f64>>> = BTreeMap::new();
+    for rec in records {
+        let i = match entries
+            .iter()
+            .position(|e| e.parameter.name == rec.param_name && e.risk.channel == rec.channel)
+        {
+            Some(i) => i,
+            None => continue,
+        };
+        let (date, secs) = parse_timestamp(&rec.timestamp, need_time)?;
+        let e = entries[i];
+        let rc = compute_risk_coord_measured(&e.parameter, &e.risk, rec.value, &rec.unit)?;
+        buckets
+            .entry((date, secs / width * width))
+            .or_insert_with(|| vec![Vec::new(); entries.len()])[i]
+            .push(rc.r);
+    }
+
+    let form = corridors.form();
+    let mut out = Vec::with_capacity(buckets.len());
+    for ((date, start), mut samples) in buckets {
+        let r: Vec<Option<f64>> = samples
+            .iter_mut()
+            .map(|s| (!s.is_empty()).then(|| cfg.aggregation.reduce(s)))
+            .collect();
+        let coords: Option<Vec<RiskCoord>> = entries
+            .iter()
+            .zip(&r)
+            .map(|(e, r)| {
+                r.map(|r| RiskCoord {
+                    r,
+                    w: e.risk.weight_w,
+                })
+            })
+            .collect();
+        let v = match coords {
+            Some(coords) => Some(compute_residual_with(form, &coords)?.v),
+            None => None,
+        };
+        out.push(WindowResidual {
+            date,
+            start,
+            samples: samples.iter().map(Vec::len).sum(),
+            r,
+            v,
+        });
+    }
+    Ok(out)
+}
+
+/// Apply V_next ≤ V_prev + ε between consecutive windows and roll the result
+/// up per day. A day is accepted only if each of its windows has a complete
+/// V and every step into one of its windows is admissible. The predecessor of
+/// a window is the latest earlier window with a V.
+pub fn daily_verdicts(windows: &[WindowResidual], cfg: &WindowConfig) -> Vec<DayVerdict> {
+    let mut days: Vec<DayVerdict> = Vec::new();
+    let mut prev = cfg.v_baseline;
+    for w in windows {
+        if days.last().map_or(true, |d| d.date != w.date) {
+            days.push(DayVerdict {
+                date: w.date,
+                accepted: true,
+                windows: 0,
+                reasons: Vec::new(),
+            });
+        }
+        let day = days.last_mut().expect("pushed above");
+        day.windows += 1;
+        let label = format!(
+            "{} window {:02}:{:02}",
+            cfg.size,
+            w.start / 3_600,
+            w.start % 3_600 / 60
+        );
+        let v = match w.v {
+            Some(v) => v,
+            None => {
+                day.accepted = false;
+                day.reasons.push(format!(
+                    "{}: not every corridor parameter was sampled",
+                    label
+                ));
+                continue;
+            }
+        };
+        if let Some(p) = prev {
+            if !is_admissible(p, v, cfg.eps) {
+                day.accepted = false;
+                day.reasons.push(format!(
+                    "{}: V rose from {:.6} to {:.6} (ε = {})",
+                    label, p, v, cfg.eps
+                ));
+            }
+        }
+        prev = Some(v);
+    }
+    days
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::corridor_shard::parse_corridor_shard;
+
+    const CORRIDORS: &str = "\
+node_id,parameter,unit,legal_limit,gold_limit,r_min,r_max,weight_w,channel
+PHX-TEST,NOx_stack,mg/Nm3,150,80,0,150,0.6,0
+PHX-TEST,PM_stack,mg/Nm3,10,5,0,10,0.4,1
+";
+
+    fn record(timestamp: &str, param: &str, channel: u32, value: f64) -> TelemetryRecord {
+        TelemetryRecord {
+            line: 0,
+            timestamp: timestamp.to_string(),
+            node_id: "PHX-TEST".to_string(),
+            channel,
+            param_name: param.to_string(),
+            value,
+            unit: "mg/Nm3".to_string(),
+            risk_r: 0.0,
+            weight_w: 0.0,
+            v_t: 0.0,
+            mode: "NORMAL".to_string(),
+            legal_ok: true,
+            gold_ok: true,
+            gate_safety_ok: true,
+        }
+    }
+
+    #[test]
+    fn test_daily_gate_rejects_worse_days() {
+        let corridors = parse_corridor_shard(CORRIDORS.as_bytes()).unwrap();
+        let mut records = Vec::new();
+        // Day 1 and 3 improve on the previous day; day 2 regresses on NOx
+        // even though its morning alone would have passed; day 4 lacks PM.
+        for (day, nox, pm) in [
+            ("01", [90.0, 60.0], 5.0),
+            ("02", [30.0, 140.0], 5.0),
+            ("03", [40.0, 40.0], 4.0),
+        ] {
+            for (hour, n) in ["08", "20"].iter().zip(nox) {
+                let ts = format!("2026-03-{}T{}:15:00Z", day, hour);
+                records.push(record(&ts, "NOx_stack", 0, n));
+                records.push(record(&ts, "PM_stack", 1, pm));
+            }
+        }
+        records.push(record("2026-03-04T09:00:00Z", "NOx_stack", 0, 10.0));
+        records.push(record("2026-03-04T09:00:00Z", "CO", 3, 1e6));
+
+        let cfg = WindowConfig::default();
+        let windows = windowed_residuals(&corridors, &records, &cfg).unwrap();
+        assert_eq!(windows.len(), 4);
+        assert!((windows[0].v.unwrap() - (0.6 * 0.5 + 0.4 * 0.5)).abs() < 1e-12);
+
+        let days = daily_verdicts(&windows, &cfg);
+        let accepted: Vec<bool> = days.iter().map(|d| d.accepted).collect();
+        assert_eq!(accepted, [true, false, true, false]);
+        assert_eq!(days[1].date.to_string(), "2026-03-02");
+
+        // Hourly windows with the worst sample: day 1 evening improves on
+        // its morning, day 2 evening does not.
+        let hourly = WindowConfig {
+            size: WindowSize::Hourly,
+            aggregation: Aggregation::Max,
+            ..WindowConfig::default()
+        };
+        let windows = windowed_residuals(&corridors, &records, &hourly).unwrap();
+        assert_eq!(windows[1].start, 20 * 3_600);
+        let days = daily_verdicts(&windows, &hourly);
+        assert!(days[0].accepted);
+        assert!(!days[1].accepted && days[1].reasons.len() == 1);
+        assert!(days[1].reasons[0].starts_with("hourly window 20:00"));
+    }
+}
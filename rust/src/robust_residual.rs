@@ -0,0 +1,309 @@
+use crate::corridor_shard::CorridorSet;
+use crate::dual_threshold::{evaluate_thresholds, within, DualThresholdReport, Measurement};
+use crate::error::EcosafetyError;
+use crate::lyapunov::{compute_risk_coord, RiskCoord};
+use crate::residual_form::compute_residual_with;
+use crate::residual_groups::{sub_residuals, SubResidual};
+use crate::telemetry_shard::{ShardError, ShardTable};
+use crate::types::Direction;
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+/// Closed interval [lo, hi].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Interval {
+    pub lo: f64,
+    pub hi: f64,
+}
+
+impl Interval {
+    /// The interval spanned by `a` and `b`, in either order.
+    pub fn new(a: f64, b: f64) -> Self {
+        Interval {
+            lo: a.min(b),
+            hi: a.max(b),
+        }
+    }
+
+    pub fn point(x: f64) -> Self {
+        Interval { lo: x, hi: x }
+    }
+
+    pub fn width(&self) -> f64 {
+        self.hi - self.lo
+    }
+}
+
+/// Measurement error of one parameter's readings.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Uncertainty {
+    /// ± a fraction of the reading (`uncertainty_bound` 0.05 = ±5 %).
+    Relative(f64),
+    /// ± a fixed amount in the parameter's unit.
+    Absolute(f64),
+}
+
+impl Uncertainty {
+    pub fn interval(&self, x: f64) -> Interval {
+        let half = match *self {
+            Uncertainty::Relative(u) => x.abs() * u,
+            Uncertainty::Absolute(u) => u,
+        };
+        Interval::new(x - half, x + half)
+    }
+}
+
+/// Per-parameter measurement uncertainty, keyed by corridor parameter name.
+/// Parameters without an entry use `default`, or are taken as exact.
+#[derive(Clone, Debug, Default)]
+pub struct UncertaintyBounds {
+    bounds: BTreeMap<String, Uncertainty>,
+    default: Option<Uncertainty>,
+}
+
+impl UncertaintyBounds {
+    pub fn with(mut self, param_name: &str, u: Uncertainty) -> Self {
+        self.bounds.insert(param_name.to_string(), u);
+        self
+    }
+
+    pub fn with_default(mut self, u: Uncertainty) -> Self {
+        self.default = Some(u);
+        self
+    }
+
+    pub fn get(&self, param_name: &str) -> Option<Uncertainty> {
+        self.bounds.get(param_name).copied().or(self.default)
+    }
+
+    pub fn len(&self) -> usize {
+        self.bounds.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bounds.is_empty()
+    }
+}
+
+/// Parse an uncertainty shard such as `wtE_emissions_safety_data.csv`: a
+/// `pollutant` (or `parameter`) column and a relative `uncertainty_bound`.
+/// Names must match the corridor parameter names to take effect.
+pub fn parse_uncertainty_shard<R: BufRead>(reader: R) -> Result<UncertaintyBounds, ShardError> {
+    let table = ShardTable::parse(reader)?;
+    let c_name =
+        table
+            .find(&["parameter", "pollutant"])
+            .ok_or_else(|| ShardError::MissingColumn {
+                column: "parameter".to_string(),
+            })?;
+    let c_bound = table.require("uncertainty_bound")?;
+
+    let mut bounds = UncertaintyBounds::default();
+    for row in &table.rows {
+        let name = row.text(c_name, "parameter")?;
+        let u = row.real(c_bound, "uncertainty_bound")?;
+        if u < 0.0 {
+            return Err(row.invalid("uncertainty_bound", &u.to_string(), "non-negative bound"));
+        }
+        if bounds
+            .bounds
+            .insert(name.to_string(), Uncertainty::Relative(u))
+            .is_some()
+        {
+            return Err(row.invalid("parameter", name, "unique parameter"));
+        }
+    }
+    if bounds.is_empty() {
+        return Err(ShardError::NoRows);
+    }
+    Ok(bounds)
+}
+
+/// Load and validate an uncertainty shard from disk.
+pub fn load_uncertainty_shard<P: AsRef<Path>>(path: P) -> Result<UncertaintyBounds, ShardError> {
+    let file = File::open(path)?;
+    parse_uncertainty_shard(BufReader::new(file))
+}
+
+/// Worst- and best-case view of one corridor parameter. `None` fields mean
+/// no measurement, in which case both checks fail.
+#[derive(Clone, Debug)]
+pub struct RobustStatus {
+    pub param_name: String,
+    pub channel: u32,
+    /// Reading ± uncertainty, in the parameter's unit.
+    pub value: Option<Interval>,
+    /// The unfavourable end of `value`: hi for MAX, lo for MIN.
+    pub worst_value: Option<f64>,
+    pub r: Option<Interval>,
+    /// r_hi < 1.
+    pub corridor_ok: bool,
+    /// `worst_value` is within the legal limit.
+    pub legal_ok: bool,
+}
+
+#[derive(Clone, Debug)]
+pub struct RobustReport {
+    pub statuses: Vec<RobustStatus>,
+    /// [V_lo, V_hi]; `None` when the nominal residual is `None`.
+    pub residual: Option<Interval>,
+    /// Sub-residuals at r_lo and at r_hi.
+    pub groups_lo: Vec<SubResidual>,
+    pub groups_hi: Vec<SubResidual>,
+}
+
+/// Propagate `bounds` through the measured values of `report` (from
+/// `evaluate_thresholds` on the same corridors). r is monotone in x and every
+/// residual form is monotone in r, so the interval ends map to [r_lo, r_hi]
+/// and [V_lo, V_hi] exactly.
+pub fn robust_report(
+    corridors: &CorridorSet,
+    report: &DualThresholdReport,
+    bounds: &UncertaintyBounds,
+) -> Result<RobustReport, EcosafetyError> {
+    let mut statuses = Vec::with_capacity(report.statuses.len());
+    let mut lo = Vec::with_capacity(report.statuses.len());
+    let mut hi = Vec::with_capacity(report.statuses.len());
+    for (entry, s) in corridors.iter().zip(&report.statuses) {
+        let p = &entry.parameter;
+        let (value, r) = match s.value {
+            None => (None, None),
+            Some(x) => {
+                let value = match bounds.get(&p.name) {
+                    Some(u) => u.interval(x),
+                    None => Interval::point(x),
+                };
+                let a = compute_risk_coord(p, &entry.risk, value.lo)?.r;
+                let b = compute_risk_coord(p, &entry.risk, value.hi)?.r;
+                (Some(value), Some(Interval::new(a, b)))
+            }
+        };
+        let worst_value = value.map(|v| match p.direction {
+            Direction::Max => v.hi,
+            Direction::Min => v.lo,
+        });
+        statuses.push(RobustStatus {
+            param_name: p.name.clone(),
+            channel: entry.risk.channel,
+            value,
+            worst_value,
+            r,
+            corridor_ok: r.is_some_and(|r| r.hi < 1.0),
+            legal_ok: worst_value.is_some_and(|x| within(p.direction, x, p.legal_limit)),
+        });
+        lo.push(r.map(|r| r.lo));
+        hi.push(r.map(|r| r.hi));
+    }
+
+    let residual = match &report.residual {
+        None => None,
+        Some(_) => {
+            let coords = |rs: &[Option<f64>]| -> Vec<RiskCoord> {
+                corridors
+                    .iter()
+                    .zip(rs)
+                    .filter_map(|(e, r)| {
+                        r.map(|r| RiskCoord {
+                            r,
+                            w: e.risk.weight_w,
+                        })
+                    })
+                    .collect()
+            };
+            let form = corridors.form();
+            let v_lo = compute_residual_with(form, &coords(&lo))?.v;
+            let v_hi = compute_residual_with(form, &coords(&hi))?.v;
+            Some(Interval::new(v_lo, v_hi))
+        }
+    };
+
+    Ok(RobustReport {
+        statuses,
+        residual,
+        groups_lo: sub_residuals(corridors, &lo)?,
+        groups_hi: sub_residuals(corridors, &hi)?,
+    })
+}
+
+/// `evaluate_thresholds` followed by `robust_report`.
+pub fn evaluate_robust(
+    corridors: &CorridorSet,
+    measurements: &[Measurement],
+    bounds: &UncertaintyBounds,
+) -> Result<(DualThresholdReport, RobustReport), EcosafetyError> {
+    let report = evaluate_thresholds(corridors, measurements)?;
+    let robust = robust_report(corridors, &report, bounds)?;
+    Ok((report, robust))
+}
+
+/// Worst-case admissibility: V_hi,next ≤ V_lo,prev + ε, so the step holds
+/// however the sensor error falls on either side.
+pub fn is_robustly_admissible(v_prev: Interval, v_next: Interval, eps: f64) -> bool {
+    v_next.hi <= v_prev.lo + eps
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::corridor_shard::parse_corridor_shard;
+    use crate::lyapunov::is_admissible;
+
+    const CORRIDORS: &str = "\
+node_id,parameter,unit,legal_limit,gold_limit,r_min,r_max,weight_w,channel
+PHX-TEST,NOx,mg/Nm3,200,150,0,200,0.6,0
+PHX-TEST,O2_stack,vol%,>6,>8,6,12,0.4,1
+";
+
+    fn readings(nox: f64, o2: f64) -> Vec<Measurement> {
+        vec![
+            Measurement {
+                param_name: "NOx".to_string(),
+                channel: 0,
+                value: nox,
+                unit: "mg/Nm3".to_string(),
+            },
+            Measurement {
+                param_name: "O2_stack".to_string(),
+                channel: 1,
+                value: o2,
+                unit: "vol%".to_string(),
+            },
+        ]
+    }
+
+    #[test]
+    fn test_noise_sized_improvement_is_not_robustly_admissible() {
+        let corridors = parse_corridor_shard(CORRIDORS.as_bytes()).unwrap();
+        let bounds = parse_uncertainty_shard(
+            include_str!("../../qpudatashards/wtE_emissions_safety_data.csv").as_bytes(),
+        )
+        .unwrap()
+        .with("O2_stack", Uncertainty::Absolute(0.1));
+        assert_eq!(bounds.get("NOx"), Some(Uncertainty::Relative(0.05)));
+
+        let (prev, prev_r) = evaluate_robust(&corridors, &readings(120.0, 9.0), &bounds).unwrap();
+        // NOx drops by 2 %, well inside the ±5 % sensor band.
+        let (next, next_r) = evaluate_robust(&corridors, &readings(117.6, 9.0), &bounds).unwrap();
+        let (v_prev, v_next) = (prev.residual.unwrap().v, next.residual.unwrap().v);
+        assert!(is_admissible(v_prev, v_next, 0.0));
+
+        let (iv_prev, iv_next) = (prev_r.residual.unwrap(), next_r.residual.unwrap());
+        assert!(iv_prev.lo < v_prev && v_prev < iv_prev.hi);
+        assert!(!is_robustly_admissible(iv_prev, iv_next, 0.0));
+
+        // A reduction larger than the combined bands does pass.
+        let (_, far_r) = evaluate_robust(&corridors, &readings(80.0, 9.0), &bounds).unwrap();
+        assert!(is_robustly_admissible(
+            iv_prev,
+            far_r.residual.unwrap(),
+            0.0
+        ));
+
+        // 195 ± 5 % may be above the legal 200 mg/Nm3.
+        let (near, near_r) = evaluate_robust(&corridors, &readings(195.0, 9.0), &bounds).unwrap();
+        assert!(near.flags.legal_ok);
+        assert!(!near_r.statuses[0].legal_ok);
+    }
+}
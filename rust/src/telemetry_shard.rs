@@ -0,0 +1,357 @@
+use std::error::Error;
+use std::fmt;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+/// Errors raised while ingesting a qpudatashard. Line numbers are 1-based and
+/// count the header, so they match what an editor shows for the CSV file.
+#[derive(Debug)]
+pub enum ShardError {
+    Io(std::io::Error),
+    EmptyShard,
+    NoRows,
+    MissingColumn {
+        column: String,
+    },
+    FieldCount {
+        line: usize,
+        expected: usize,
+        found: usize,
+    },
+    MissingField {
+        line: usize,
+        field: String,
+    },
+    InvalidField {
+        line: usize,
+        field: String,
+        value: String,
+        expected: &'static str,
+    },
+}
+
+impl fmt::Display for ShardError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ShardError::Io(e) => write!(f, "IO error: {}", e),
+            ShardError::EmptyShard => write!(f, "shard has no header row"),
+            ShardError::NoRows => write!(f, "shard has no data rows (no corridor, no deployment)"),
+            ShardError::MissingColumn { column } => {
+                write!(f, "header is missing required column `{}`", column)
+            }
+            ShardError::FieldCount {
+                line,
+                expected,
+                found,
+            } => write!(
+                f,
+                "line {}: expected {} fields, found {}",
+                line, expected, found
+            ),
+            ShardError::MissingField { line, field } => {
+                write!(f, "line {}: missing value for `{}`", line, field)
+            }
+            ShardError::InvalidField {
+                line,
+                field,
+                value,
+                expected,
+            } => write!(
+                f,
+                "line {}: invalid `{}` value {:?} (expected {})",
+                line, field, value, expected
+            ),
+        }
+    }
+}
+
+impl Error for ShardError {}
+
+impl From<std::io::Error> for ShardError {
+    fn from(err: std::io::Error) -> Self {
+        ShardError::Io(err)
+    }
+}
+
+/// Minimal CSV splitter that supports quoted fields.
+fn split_csv_line(line: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+
+    for c in line.chars() {
+        match c {
+            '"' => {
+                in_quotes = !in_quotes;
+            }
+            ',' if !in_quotes => {
+                fields.push(current.trim().to_string());
+                current.clear();
+            }
+            _ => current.push(c),
+        }
+    }
+    fields.push(current.trim().to_string());
+    fields
+}
+
+/// Raw shard contents: the header plus every non-blank data row, with
+/// columns addressed by name so loaders tolerate column reordering.
+pub(crate) struct ShardTable {
+    columns: Vec<String>,
+    pub(crate) rows: Vec<ShardRow>,
+}
+
+pub(crate) struct ShardRow {
+    pub(crate) line: usize,
+    fields: Vec<String>,
+}
+
+impl ShardTable {
+    pub(crate) fn parse<R: BufRead>(reader: R) -> Result<Self, ShardError> {
+        let mut lines = reader.lines();
+        let columns = match lines.next() {
+            Some(h) => split_csv_line(h?.trim_start_matches('\u{feff}')),
+            None => return Err(ShardError::EmptyShard),
+        };
+
+        let mut rows = Vec::new();
+        for (idx, line_res) in lines.enumerate() {
+            let line = line_res?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let fields = split_csv_line(&line);
+            if fields.len() != columns.len() {
+                return Err(ShardError::FieldCount {
+                    line: idx + 2,
+                    expected: columns.len(),
+                    found: fields.len(),
+                });
+            }
+            rows.push(ShardRow {
+                line: idx + 2,
+                fields,
+            });
+        }
+
+        Ok(ShardTable { columns, rows })
+    }
+
+    /// Index of the first column matching any of `names` (case-insensitive).
+    pub(crate) fn find(&self, names: &[&str]) -> Option<usize> {
+        names.iter().find_map(|name| {
+            self.columns
+                .iter()
+                .position(|c| c.eq_ignore_ascii_case(name))
+        })
+    }
+
+    /// Columns whose name starts with `prefix`, as (name without prefix, index).
+    pub(crate) fn prefixed(&self, prefix: &str) -> Vec<(String, usize)> {
+        self.columns
+            .iter()
+            .enumerate()
+            .filter_map(|(i, c)| c.strip_prefix(prefix).map(|name| (name.to_string(), i)))
+            .collect()
+    }
+
+    pub(crate) fn require(&self, name: &str) -> Result<usize, ShardError> {
+        self.find(&[name]).ok_or_else(|| ShardError::MissingColumn {
+            column: name.to_string(),
+        })
+    }
+}
+
+impl ShardRow {
+    pub(crate) fn invalid(&self, field: &str, value: &str, expected: &'static str) -> ShardError {
+        ShardError::InvalidField {
+            line: self.line,
+            field: field.to_string(),
+            value: value.to_string(),
+            expected,
+        }
+    }
+
+    /// Raw field text; empty fields are reported as missing.
+    pub(crate) fn text(&self, col: usize, field: &str) -> Result<&str, ShardError> {
+        let value = self.fields[col].as_str();
+        if value.is_empty() {
+            return Err(ShardError::MissingField {
+                line: self.line,
+                field: field.to_string(),
+            });
+        }
+        Ok(value)
+    }
+
+    pub(crate) fn real(&self, col: usize, field: &str) -> Result<f64, ShardError> {
+        let value = self.text(col, field)?;
+        match value.parse::<f64>() {
+            Ok(x) if x.is_finite() => Ok(x),
+            _ => Err(self.invalid(field, value, "finite number")),
+        }
+    }
+
+    pub(crate) fn index(&self, col: usize, field: &str) -> Result<u32, ShardError> {
+        let value = self.text(col, field)?;
+        value
+            .parse::<u32>()
+            .map_err(|_| self.invalid(field, value, "non-negative integer"))
+    }
+
+    pub(crate) fn flag(&self, col: usize, field: &str) -> Result<bool, ShardError> {
+        let value = self.text(col, field)?;
+        match value.to_ascii_lowercase().as_str() {
+            "true" => Ok(true),
+            "false" => Ok(false),
+            _ => Err(self.invalid(field, value, "true or false")),
+        }
+    }
+}
+
+/// One row of a CybocinderPhoenixTelemetry*.csv residual shard.
+#[derive(Clone, Debug)]
+pub struct TelemetryRecord {
+    pub line: usize,
+    pub timestamp: String,
+    pub node_id: String,
+    pub channel: u32,
+    pub param_name: String,
+    pub value: f64,
+    pub unit: String,
+    pub risk_r: f64,
+    pub weight_w: f64,
+    pub v_t: f64,
+    pub mode: String,
+    pub legal_ok: bool,
+    pub gold_ok: bool,
+    pub gate_safety_ok: bool,
+}
+
+/// Parse a telemetry shard. Every column of the template is mandatory;
+/// the first missing or mis-typed field aborts ingestion with its line number.
+pub fn parse_telemetry_shard<R: BufRead>(reader: R) -> Result<Vec<TelemetryRecord>, ShardError> {
+    let table = ShardTable::parse(reader)?;
+
+    let c_timestamp = table.require("timestamp")?;
+    let c_node = table.require("node_id")?;
+    let c_channel = table.require("channel")?;
+    let c_param = table.require("param_name")?;
+    let c_value = table.require("value")?;
+    let c_unit = table.require("unit")?;
+    let c_r = table.require("risk_r")?;
+    let c_w = table.require("weight_w")?;
+    let c_v = table.require("V_t")?;
+    let c_mode = table.require("mode")?;
+    let c_legal = table.require("legal_ok")?;
+    let c_gold = table.require("gold_ok")?;
+    let c_safety = table.require("gate_safety_ok")?;
+
+    let mut records = Vec::with_capacity(table.rows.len());
+    for row in &table.rows {
+        let risk_r = row.real(c_r, "risk_r")?;
+        if !(0.0..=1.0).contains(&risk_r) {
+            return Err(row.invalid("risk_r", &risk_r.to_string(), "risk coordinate in [0,1]"));
+        }
+        let weight_w = row.real(c_w, "weight_w")?;
+        if weight_w < 0.0 {
+            return Err(row.invalid("weight_w", &weight_w.to_string(), "non-negative weight"));
+        }
+        let unit = row.text(c_unit, "unit")?;
+        let v_t = row.real(c_v, "V_t")?;
+        if v_t < 0.0 {
+            return Err(row.invalid("V_t", &v_t.to_string(), "non-negative residual"));
+        }
+
+        records.push(TelemetryRecord {
+            line: row.line,
+            timestamp: row.text(c_timestamp, "timestamp")?.to_string(),
+            node_id: row.text(c_node, "node_id")?.to_string(),
+            channel: row.index(c_channel, "channel")?,
+            param_name: row.text(c_param, "param_name")?.to_string(),
+            value: row.real(c_value, "value")?,
+            unit: unit.to_string(),
+            risk_r,
+            weight_w,
+            v_t,
+            mode: row.text(c_mode, "mode")?.to_string(),
+            legal_ok: row.flag(c_legal, "legal_ok")?,
+            gold_ok: row.flag(c_gold, "gold_ok")?,
+            gate_safety_ok: row.flag(c_safety, "gate_safety_ok")?,
+        });
+    }
+
+    Ok(records)
+}
+
+/// Load and validate a telemetry shard from disk.
+pub fn load_telemetry_shard<P: AsRef<Path>>(path: P) -> Result<Vec<TelemetryRecord>, ShardError> {
+    let file = File::open(path)?;
+    parse_telemetry_shard(BufReader::new(file))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HEADER: &str = "timestamp,node_id,channel,param_name,value,unit,risk_r,weight_w,V_t,mode,legal_ok,gold_ok,gate_safety_ok";
+
+    #[test]
+    fn test_parse_valid_row() {
+        let csv = format!(
+            "{}\n2026-01-17T19:27:00Z,PHX-CYBO-1,0,NOx_stack,95,mg/Nm3,0.63,0.22,0.41,OPERATE,true,true,true\n",
+            HEADER
+        );
+        let recs = parse_telemetry_shard(csv.as_bytes()).unwrap();
+        assert_eq!(recs.len(), 1);
+        assert_eq!(recs[0].line, 2);
+        assert_eq!(recs[0].param_name, "NOx_stack");
+        assert!((recs[0].v_t - 0.41).abs() < 1e-12);
+        assert!(recs[0].gate_safety_ok);
+    }
+
+    #[test]
+    fn test_unknown_unit_is_kept_as_written() {
+        let csv = format!(
+            "{}\n2026-01-17T19:27:00Z,PHX-CYBO-1,7,O2,6,%,0.2,0.05,0.41,OPERATE,true,true,true\n",
+            HEADER
+        );
+        let recs = parse_telemetry_shard(csv.as_bytes()).unwrap();
+        assert_eq!(recs[0].unit, "%");
+    }
+
+    #[test]
+    fn test_header_only_template() {
+        let recs = parse_telemetry_shard(HEADER.as_bytes()).unwrap();
+        assert!(recs.is_empty());
+    }
+
+    #[test]
+    fn test_missing_and_mistyped_fields_report_line() {
+        let csv = format!(
+            "{}\n2026-01-17T19:27:00Z,PHX-CYBO-1,0,NOx_stack,95,mg/Nm3,0.63,0.22,0.41,OPERATE,true,true,true\n2026-01-17T19:27:10Z,PHX-CYBO-1,0,NOx_stack,,mg/Nm3,0.63,0.22,0.41,OPERATE,true,true,true\n",
+            HEADER
+        );
+        match parse_telemetry_shard(csv.as_bytes()) {
+            Err(ShardError::MissingField { line, field }) => {
+                assert_eq!(line, 3);
+                assert_eq!(field, "value");
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        let csv = format!(
+            "{}\n2026-01-17T19:27:00Z,PHX-CYBO-1,0,NOx_stack,95,mg/Nm3,0.63,0.22,0.41,OPERATE,yes,true,true\n",
+            HEADER
+        );
+        match parse_telemetry_shard(csv.as_bytes()) {
+            Err(ShardError::InvalidField { line, field, .. }) => {
+                assert_eq!(line, 2);
+                assert_eq!(field, "legal_ok");
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+}
@@ -1,4 +1,142 @@
-use std::collections::HashMap;
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+/// A shard or config value that matches none of the spellings of an enum.
+#[derive(Clone, Debug, PartialEq)]
+pub struct UnknownVariant {
+    pub kind: &'static str,
+    pub value: String,
+}
+
+impl fmt::Display for UnknownVariant {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unknown {} {:?}", self.kind, self.value)
+    }
+}
+
+impl Error for UnknownVariant {}
+
+/// Parameter direction: MAX = safe if x ≤ limit, MIN = safe if x ≥ limit.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Direction {
+    Max,
+    Min,
+}
+
+impl Direction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Direction::Max => "MAX",
+            Direction::Min => "MIN",
+        }
+    }
+}
+
+impl FromStr for Direction {
+    type Err = UnknownVariant;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_uppercase().as_str() {
+            "MAX" => Ok(Direction::Max),
+            "MIN" => Ok(Direction::Min),
+            _ => Err(UnknownVariant {
+                kind: "direction",
+                value: s.to_string(),
+            }),
+        }
+    }
+}
+
+/// LCA functional unit.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum FunctionalUnit {
+    MswTon,
+    EnergyMwh,
+    ResourceKg,
+}
+
+impl FunctionalUnit {
+    pub const ALL: [FunctionalUnit; 3] = [
+        FunctionalUnit::MswTon,
+        FunctionalUnit::EnergyMwh,
+        FunctionalUnit::ResourceKg,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FunctionalUnit::MswTon => "MSW_TON",
+            FunctionalUnit::EnergyMwh => "ENERGY_MWH",
+            FunctionalUnit::ResourceKg => "RESOURCE_KG",
+        }
+    }
+}
+
+impl FromStr for FunctionalUnit {
+    type Err = UnknownVariant;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_uppercase().as_str() {
+            "MSW_TON" | "1_TON_MSW" => Ok(FunctionalUnit::MswTon),
+            "ENERGY_MWH" | "1_MWH" | "1_MWH_DELIVERED" => Ok(FunctionalUnit::EnergyMwh),
+            "RESOURCE_KG" | "1_KG" | "1_KG_RECOVERED" => Ok(FunctionalUnit::ResourceKg),
+            _ => Err(UnknownVariant {
+                kind: "functional unit",
+                value: s.to_string(),
+            }),
+        }
+    }
+}
+
+/// Side of an LCA comparison: baseline (STATUS_QUO) or candidate (CYBOCINDER).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ScenarioMode {
+    StatusQuo,
+    Cybocinder,
+}
+
+impl ScenarioMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ScenarioMode::StatusQuo => "STATUS_QUO",
+            ScenarioMode::Cybocinder => "CYBOCINDER",
+        }
+    }
+}
+
+impl FromStr for ScenarioMode {
+    type Err = UnknownVariant;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_uppercase().as_str() {
+            "STATUS_QUO" | "STATUSQUO" | "BASELINE" | "LANDFILL" => Ok(ScenarioMode::StatusQuo),
+            "CYBOCINDER" => Ok(ScenarioMode::Cybocinder),
+            _ => Err(UnknownVariant {
+                kind: "scenario mode",
+                value: s.to_string(),
+            }),
+        }
+    }
+}
+
+impl fmt::Display for Direction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl fmt::Display for FunctionalUnit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl fmt::Display for ScenarioMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
 
 #[derive(Clone, Debug)]
 pub struct Parameter {
@@ -8,7 +146,7 @@ pub struct Parameter {
     pub domain_max: f64,
     pub legal_limit: Option<f64>,
     pub gold_limit: Option<f64>,
-    pub direction_max: bool, // true = MAX, false = MIN
+    pub direction: Direction,
 }
 
 #[derive(Clone, Debug)]
@@ -25,12 +163,51 @@ pub struct RiskCoordinateDef {
 pub struct LcaScenario {
     pub scenario_id: String,
     pub region_id: String,
-    pub functional_unit: String, // "MSW_TON", "ENERGY_MWH", "RESOURCE_KG"
-    pub mode: String,            // "STATUS_QUO" or "CYBOCINDER"
+    pub functional_unit: FunctionalUnit,
+    pub mode: ScenarioMode,
     pub gwp_kg_co2eq: f64,
     pub grid_gco2_per_kwh: f64,
-    pub landfill_ref_gwp_kgco2_per_ton: f64,
-    pub avoided_virgin_metal_kgco2eq_per_kg: f64,
-    pub energy_recovery_efficiency: f64,
-    pub recycling_rate: f64,
+    // Avoided-burden parameters; `None` when the shard layout has no column.
+    pub landfill_ref_gwp_kgco2_per_ton: Option<f64>,
+    pub avoided_virgin_metal_kgco2eq_per_kg: Option<f64>,
+    pub energy_recovery_efficiency: Option<f64>,
+    pub recycling_rate: Option<f64>,
+    pub other_impacts: BTreeMap<String, f64>, // e.g. "acidification", "PM", "ecotoxicity"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_enums_round_trip_through_display() {
+        for d in [Direction::Max, Direction::Min] {
+            assert_eq!(d.to_string().parse::<Direction>(), Ok(d));
+        }
+        for fu in FunctionalUnit::ALL {
+            assert_eq!(fu.to_string().parse::<FunctionalUnit>(), Ok(fu));
+        }
+        for m in [ScenarioMode::StatusQuo, ScenarioMode::Cybocinder] {
+            assert_eq!(m.to_string().parse::<ScenarioMode>(), Ok(m));
+        }
+        // Shard spellings and case are accepted on input.
+        assert_eq!(" min ".parse::<Direction>(), Ok(Direction::Min));
+        assert_eq!("1_ton_MSW".parse(), Ok(FunctionalUnit::MswTon));
+        assert_eq!("Landfill".parse(), Ok(ScenarioMode::StatusQuo));
+    }
+
+    #[test]
+    fn test_unknown_values_are_rejected() {
+        assert_eq!(
+            ">".parse::<Direction>(),
+            Err(UnknownVariant {
+                kind: "direction",
+                value: ">".to_string(),
+            })
+        );
+        let err = "1_GJ".parse::<FunctionalUnit>().unwrap_err();
+        assert_eq!(err.kind, "functional unit");
+        assert_eq!(err.to_string(), "unknown functional unit \"1_GJ\"");
+        assert!("".parse::<ScenarioMode>().is_err());
+    }
 }
@@ -0,0 +1,191 @@
+use std::error::Error;
+use std::fmt;
+
+/// Physical dimension of a shard unit. Normalised stack concentrations
+/// (per Nm³), ambient concentrations (per m³) and dioxin toxic equivalents
+/// are kept apart on purpose: converting between them needs flue-gas
+/// conditions or TEF tables, not a scale factor.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Dimension {
+    StackMassConcentration,
+    StackTeqConcentration,
+    AmbientMassConcentration,
+    VolumeFraction,
+    Temperature,
+    Time,
+}
+
+/// A parsed unit: SI value = x · scale + offset.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Unit {
+    pub symbol: &'static str,
+    pub dimension: Dimension,
+    pub scale: f64,
+    pub offset: f64,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum UnitError {
+    Unknown(String),
+    /// A spelling with several readings, e.g. bare `%` (vol%, wt% or RH).
+    Ambiguous(String),
+    Incompatible {
+        from: String,
+        to: String,
+    },
+}
+
+impl fmt::Display for UnitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UnitError::Unknown(u) => write!(f, "unknown unit {:?}", u),
+            UnitError::Ambiguous(u) => write!(f, "ambiguous unit {:?}", u),
+            UnitError::Incompatible { from, to } => {
+                write!(f, "cannot convert {} to {}: different dimensions", from, to)
+            }
+        }
+    }
+}
+
+impl Error for UnitError {}
+
+const fn unit(symbol: &'static str, dimension: Dimension, scale: f64) -> Unit {
+    Unit {
+        symbol,
+        dimension,
+        scale,
+        offset: 0.0,
+    }
+}
+
+impl Unit {
+    /// Parse a UCUM-style or shard spelling (`mg/Nm3`, `ng I-TEQ/Nm3`, `°C`,
+    /// `Cel`, `vol%`, `s`, `ug/m3`, `ugm3`, `[ppb]`, ...).
+    pub fn parse(s: &str) -> Result<Unit, UnitError> {
+        use Dimension::*;
+        let norm: String = s
+            .trim()
+            .replace(['µ', 'μ'], "u")
+            .replace("^3", "3")
+            .replace('³', "3")
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .collect();
+        let u = match norm.as_str() {
+            "g/Nm3" | "gNm3" => unit("g/Nm3", StackMassConcentration, 1e-3),
+            "mg/Nm3" | "mgNm3" => unit("mg/Nm3", StackMassConcentration, 1e-6),
+            "ug/Nm3" | "ugNm3" => unit("ug/Nm3", StackMassConcentration, 1e-9),
+            "ng/Nm3" | "ngNm3" => unit("ng/Nm3", StackMassConcentration, 1e-12),
+            "ngI-TEQ/Nm3" | "ngTEQ/Nm3" => unit("ng I-TEQ/Nm3", StackTeqConcentration, 1e-12),
+            "pgI-TEQ/Nm3" | "pgTEQ/Nm3" => unit("pg I-TEQ/Nm3", StackTeqConcentration, 1e-15),
+            "fgI-TEQ/Nm3" | "fgTEQ/Nm3" => unit("fg I-TEQ/Nm3", StackTeqConcentration, 1e-18),
+            "mg/m3" | "mgm3" => unit("mg/m3", AmbientMassConcentration, 1e-6),
+            "ug/m3" | "ugm3" => unit("ug/m3", AmbientMassConcentration, 1e-9),
+            "ng/m3" | "ngm3" => unit("ng/m3", AmbientMassConcentration, 1e-12),
+            "ppm" | "[ppm]" => unit("ppm", VolumeFraction, 1e-6),
+            "ppb" | "[ppb]" => unit("ppb", VolumeFraction, 1e-9),
+            "vol%" | "%vol" => unit("vol%", VolumeFraction, 1e-2),
+            "%" => return Err(UnitError::Ambiguous(s.to_string())),
+            "°C" | "degC" | "Cel" => Unit {
+                symbol: "°C",
+                dimension: Temperature,
+                scale: 1.0,
+                offset: 273.15,
+            },
+            "K" => unit("K", Temperature, 1.0),
+            "s" => unit("s", Time, 1.0),
+            "min" => unit("min", Time, 60.0),
+            "h" => unit("h", Time, 3600.0),
+            _ => return Err(UnitError::Unknown(s.to_string())),
+        };
+        Ok(u)
+    }
+
+    pub fn is_compatible(&self, other: &Unit) -> bool {
+        self.dimension == other.dimension
+    }
+
+    /// Convert `x` expressed in `self` into `to`.
+    pub fn convert(&self, x: f64, to: &Unit) -> Result<f64, UnitError> {
+        if !self.is_compatible(to) {
+            return Err(UnitError::Incompatible {
+                from: self.symbol.to_string(),
+                to: to.symbol.to_string(),
+            });
+        }
+        let si = x * self.scale + self.offset;
+        Ok((si - to.offset) / to.scale)
+    }
+}
+
+/// A value with its unit.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Quantity {
+    pub value: f64,
+    pub unit: Unit,
+}
+
+impl Quantity {
+    pub fn parse(value: f64, unit: &str) -> Result<Quantity, UnitError> {
+        Ok(Quantity {
+            value,
+            unit: Unit::parse(unit)?,
+        })
+    }
+
+    pub fn to(&self, unit: &Unit) -> Result<Quantity, UnitError> {
+        Ok(Quantity {
+            value: self.unit.convert(self.value, unit)?,
+            unit: *unit,
+        })
+    }
+}
+
+/// Convert `x` from unit string `from` into unit string `to`.
+pub fn convert(x: f64, from: &str, to: &str) -> Result<f64, UnitError> {
+    Unit::parse(from)?.convert(x, &Unit::parse(to)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shard_spellings_parse() {
+        for u in [
+            "mg/Nm3",
+            "ng I-TEQ/Nm3",
+            "°C",
+            "vol%",
+            "s",
+            "ug/m3",
+            "ugm3",
+            "ppb",
+            "µg/Nm3",
+        ] {
+            assert!(Unit::parse(u).is_ok(), "{}", u);
+        }
+        assert!(Unit::parse("furlong").is_err());
+        assert_eq!(
+            Unit::parse(" % "),
+            Err(UnitError::Ambiguous(" % ".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_convert_compatible() {
+        let mg = convert(95_000.0, "µg/Nm3", "mg/Nm3").unwrap();
+        assert!((mg - 95.0).abs() < 1e-9);
+        let k = convert(850.0, "°C", "K").unwrap();
+        assert!((k - 1123.15).abs() < 1e-9);
+        let ppm = convert(6.0, "vol%", "ppm").unwrap();
+        assert!((ppm - 60_000.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_refuse_incompatible() {
+        assert!(convert(10.0, "ug/m3", "mg/Nm3").is_err());
+        assert!(convert(0.1, "ng I-TEQ/Nm3", "mg/Nm3").is_err());
+        assert!(convert(2.0, "s", "°C").is_err());
+    }
+}
@@ -0,0 +1,641 @@
+//! Bounded-state verification of the residual and gate math.
+//!
+//! Each invariant is checked twice: by exhaustive enumeration over a small
+//! grid that includes every boundary value, and by seeded property-based
+//! generation with greedy shrinking, so a regression is reported as a
+//! minimal counterexample rather than a random one.
+//!
+//! Invariants:
+//! - r ∈ [0, 1] for every finite or infinite reading (NaN is excluded: the
+//!   shard loaders reject non-finite values before they reach the math);
+//! - V ∈ [0, Σw];
+//! - scaleup_gate ⇒ safety_gate;
+//! - any predicted r ≥ 1 makes the step non-admissible.
+
+use econet_cybocinder_phoenix::corridor_shard::{parse_corridor_shard, CorridorSet};
+use econet_cybocinder_phoenix::dual_threshold::{evaluate_thresholds, Measurement};
+use econet_cybocinder_phoenix::error::EcosafetyError;
+use econet_cybocinder_phoenix::gates::{compute_gates, ResidualFlags};
+use econet_cybocinder_phoenix::lyapunov::{
+    compute_residual, compute_risk_coord, is_admissible, RiskCoord,
+};
+use econet_cybocinder_phoenix::lyapunov_controller::{LyapunovController, PlantModel};
+use econet_cybocinder_phoenix::residual_form::{CorridorForm, Quadratic};
+use econet_cybocinder_phoenix::rng::SplitMix64;
+use econet_cybocinder_phoenix::types::{Direction, Parameter, RiskCoordinateDef};
+use std::fmt::Debug;
+
+// ---------------------------------------------------------------------------
+// Property runner with shrinking
+// ---------------------------------------------------------------------------
+
+const CASES: usize = 2000;
+const MAX_SHRINK_STEPS: usize = 10_000;
+
+#[derive(Debug)]
+struct Counterexample<T> {
+    seed: u64,
+    case: usize,
+    original: T,
+    shrunk: T,
+    message: String,
+    shrink_steps: usize,
+}
+
+/// Run `prop` on `cases` generated inputs. On the first failure, repeatedly
+/// replace the input with the first shrink candidate that still fails.
+fn find_counterexample<T, G, S, P>(
+    seed: u64,
+    cases: usize,
+    gen: G,
+    shrink: S,
+    prop: P,
+) -> Option<Counterexample<T>>
+where
+    T: Clone + Debug,
+    G: Fn(&mut SplitMix64) -> T,
+    S: Fn(&T) -> Vec<T>,
+    P: Fn(&T) -> Result<(), String>,
+{
+    let mut rng = SplitMix64::new(seed);
+    for case in 0..cases {
+        let input = gen(&mut rng);
+        let Err(message) = prop(&input) else {
+            continue;
+        };
+        let mut shrunk = input.clone();
+        let mut message = message;
+        let mut steps = 0;
+        'shrink: while steps < MAX_SHRINK_STEPS {
+            for candidate in shrink(&shrunk) {
+                if let Err(m) = prop(&candidate) {
+                    shrunk = candidate;
+                    message = m;
+                    steps += 1;
+                    continue 'shrink;
+                }
+            }
+            break;
+        }
+        return Some(Counterexample {
+            seed,
+            case,
+            original: input,
+            shrunk,
+            message,
+            shrink_steps: steps,
+        });
+    }
+    None
+}
+
+fn forall<T, G, S, P>(name: &str, seed: u64, gen: G, shrink: S, prop: P)
+where
+    T: Clone + Debug,
+    G: Fn(&mut SplitMix64) -> T,
+    S: Fn(&T) -> Vec<T>,
+    P: Fn(&T) -> Result<(), String>,
+{
+    if let Some(cx) = find_counterexample(seed, CASES, gen, shrink, prop) {
+        panic!(
+            "property `{}` failed (seed {}, case {}): {}\nshrunk in {} steps to {:#?}\noriginal {:#?}",
+            name, cx.seed, cx.case, cx.message, cx.shrink_steps, cx.shrunk, cx.original
+        );
+    }
+}
+
+/// Decimal places needed to write `x` exactly (16 if more).
+fn decimals(x: f64) -> i32 {
+    (0..16)
+        .find(|d| {
+            let scale = 10f64.powi(*d);
+            (x * scale).round() / scale == x
+        })
+        .unwrap_or(16)
+}
+
+/// Simpler values first: 0, ±1, truncation, fewer decimals, then halving
+/// (stopped at magnitude 1 so shrinking does not walk into subnormals). A
+/// candidate must be smaller in magnitude or need fewer decimals.
+fn shrink_f64(x: f64) -> Vec<f64> {
+    let mut candidates = vec![0.0, 1.0f64.copysign(x), x.trunc()];
+    for digits in 1..=3 {
+        let scale = 10f64.powi(digits);
+        candidates.push((x * scale).round() / scale);
+    }
+    if x.abs() >= 2.0 {
+        candidates.push(x / 2.0);
+    }
+    let mut out = Vec::new();
+    for c in candidates {
+        let simpler = c.abs() < x.abs() || decimals(c) < decimals(x);
+        if c != x && c.is_finite() && simpler && !out.contains(&c) {
+            out.push(c);
+        }
+    }
+    out
+}
+
+/// Drop one element, or shrink one element in place.
+fn shrink_vec<T: Clone>(xs: &[T], shrink: impl Fn(&T) -> Vec<T>) -> Vec<Vec<T>> {
+    let mut out = Vec::new();
+    if xs.len() > 1 {
+        for i in 0..xs.len() {
+            let mut smaller = xs.to_vec();
+            smaller.remove(i);
+            out.push(smaller);
+        }
+    }
+    for (i, x) in xs.iter().enumerate() {
+        for s in shrink(x) {
+            let mut next = xs.to_vec();
+            next[i] = s;
+            out.push(next);
+        }
+    }
+    out
+}
+
+// ---------------------------------------------------------------------------
+// Fixtures
+// ---------------------------------------------------------------------------
+
+fn parameter(direction: Direction) -> Parameter {
+    Parameter {
+        name: "x".to_string(),
+        unit: "mg/Nm3".to_string(),
+        domain_min: 0.0,
+        domain_max: f64::INFINITY,
+        legal_limit: None,
+        gold_limit: None,
+        direction,
+    }
+}
+
+fn risk_def(r_min: f64, r_max: f64, weight_w: f64) -> RiskCoordinateDef {
+    RiskCoordinateDef {
+        id: 0,
+        param_name: "x".to_string(),
+        r_min,
+        r_max,
+        weight_w,
+        channel: 0,
+    }
+}
+
+/// Readings that hit every boundary of a [r_min, r_max] band.
+fn boundary_readings(r_min: f64, r_max: f64) -> Vec<f64> {
+    let mid = r_min + (r_max - r_min) / 2.0;
+    vec![
+        f64::NEG_INFINITY,
+        -1e12,
+        r_min - 1.0,
+        r_min,
+        mid,
+        r_max,
+        r_max + 1e-9,
+        r_max + 1.0,
+        1e12,
+        f64::INFINITY,
+    ]
+}
+
+const CORRIDORS: &str = "\
+node_id,parameter,unit,legal_limit,gold_limit,r_min,r_max,weight_w,channel
+PHX-TEST,NOx_stack,mg/Nm3,150,80,0,150,0.4,0
+PHX-TEST,PM_stack,mg/Nm3,10,5,0,10,0.3,1
+PHX-TEST,O2_stack,vol%,>6,>8,6,12,0.3,2
+";
+
+fn corridors() -> CorridorSet {
+    parse_corridor_shard(CORRIDORS.as_bytes()).unwrap()
+}
+
+/// Plant whose prediction is the move itself: a full set of readings.
+struct Echo;
+
+impl PlantModel for Echo {
+    type State = ();
+    type Move = Vec<Measurement>;
+
+    fn predict(&self, _: &(), mv: &Vec<Measurement>) -> Result<Vec<Measurement>, EcosafetyError> {
+        Ok(mv.clone())
+    }
+}
+
+fn readings(values: &[f64; 3]) -> Vec<Measurement> {
+    [
+        ("NOx_stack", "mg/Nm3"),
+        ("PM_stack", "mg/Nm3"),
+        ("O2_stack", "vol%"),
+    ]
+    .iter()
+    .zip(values)
+    .enumerate()
+    .map(|(channel, ((name, unit), value))| Measurement {
+        param_name: name.to_string(),
+        channel: channel as u32,
+        value: *value,
+        unit: unit.to_string(),
+    })
+    .collect()
+}
+
+/// Independent r for the fixture corridors, straight from the definitions.
+fn fixture_r(corridors: &CorridorSet, values: &[f64; 3]) -> Vec<f64> {
+    readings(values)
+        .iter()
+        .map(|m| {
+            let e = corridors.get(&m.param_name, m.channel).unwrap();
+            compute_risk_coord(&e.parameter, &e.risk, m.value)
+                .unwrap()
+                .r
+        })
+        .collect()
+}
+
+// ---------------------------------------------------------------------------
+// Invariant checks shared by enumeration and generation
+// ---------------------------------------------------------------------------
+
+fn check_r_bounds(
+    direction: Direction,
+    r_min: f64,
+    r_max: f64,
+    w: f64,
+    x: f64,
+) -> Result<(), String> {
+    let rc = compute_risk_coord(&parameter(direction), &risk_def(r_min, r_max, w), x)
+        .map_err(|e| format!("unexpected error {}", e))?;
+    if !(0.0..=1.0).contains(&rc.r) {
+        return Err(format!("r = {} outside [0, 1]", rc.r));
+    }
+    Ok(())
+}
+
+fn check_v_bounds(coords: &[RiskCoord]) -> Result<(), String> {
+    let state = compute_residual(coords).map_err(|e| format!("unexpected error {}", e))?;
+    let total_w: f64 = coords.iter().map(|c| c.w).sum();
+    if !(0.0..=total_w).contains(&state.v) {
+        return Err(format!("V = {} outside [0, Σw = {}]", state.v, total_w));
+    }
+    Ok(())
+}
+
+#[derive(Clone, Debug)]
+struct GateCase {
+    flags: [bool; 5], // corridor, legal, gold, lca, pilot
+    v_prev: f64,
+    v_next: f64,
+    eps: f64,
+}
+
+fn check_gates(c: &GateCase) -> Result<(), String> {
+    let [corridor_ok, legal_ok, gold_ok, lca_ok, pilot_ok] = c.flags;
+    let flags = ResidualFlags {
+        corridor_ok,
+        legal_ok,
+        gold_ok,
+    };
+    let g = compute_gates(&flags, c.v_prev, c.v_next, c.eps, lca_ok, pilot_ok);
+    if g.scaleup_gate && !g.safety_gate {
+        return Err("scale-up gate open while safety gate closed".to_string());
+    }
+    if g.safety_gate && !(corridor_ok && legal_ok && is_admissible(c.v_prev, c.v_next, c.eps)) {
+        return Err("safety gate open without corridor, legal and V checks".to_string());
+    }
+    Ok(())
+}
+
+fn check_hard_violation(
+    corridors: &CorridorSet,
+    values: &[f64; 3],
+    v_t: f64,
+) -> Result<(), String> {
+    let rs = fixture_r(corridors, values);
+    if rs.iter().all(|r| *r < 1.0) {
+        return Ok(());
+    }
+    let controller = LyapunovController::new(corridors.clone(), Echo, 0.0);
+    let decision = controller.is_admissible_move(&(), &readings(values), v_t);
+    if decision.admissible {
+        return Err(format!("r = {:?} but the move was admitted", rs));
+    }
+    let report = evaluate_thresholds(corridors, &readings(values)).unwrap();
+    if report.gates(v_t, 0.0, true, true).safety_gate {
+        return Err(format!("r = {:?} but the safety gate is open", rs));
+    }
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Exhaustive bounded enumeration
+// ---------------------------------------------------------------------------
+
+#[test]
+fn exhaustive_r_in_unit_interval() {
+    let bounds = [-10.0, 0.0, 1.0, 150.0];
+    for direction in [Direction::Max, Direction::Min] {
+        for &r_min in &bounds {
+            for &r_max in bounds.iter().filter(|b| **b > r_min) {
+                for w in [0.0, 0.5, 1.0, 7.0] {
+                    for x in boundary_readings(r_min, r_max) {
+                        if let Err(e) = check_r_bounds(direction, r_min, r_max, w, x) {
+                            panic!(
+                                "{:?} [{}, {}] w={} x={}: {}",
+                                direction, r_min, r_max, w, x, e
+                            );
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn exhaustive_v_within_weight_sum() {
+    let rs = [0.0, 0.25, 0.5, 1.0];
+    let ws = [0.0, 0.5, 1.0, 2.0];
+    let pairs: Vec<RiskCoord> = rs
+        .iter()
+        .flat_map(|&r| ws.iter().map(move |&w| RiskCoord { r, w }))
+        .collect();
+    for a in &pairs {
+        check_v_bounds(std::slice::from_ref(a)).unwrap();
+        for b in &pairs {
+            check_v_bounds(&[a.clone(), b.clone()]).unwrap();
+            for c in &pairs {
+                let coords = [a.clone(), b.clone(), c.clone()];
+                if let Err(e) = check_v_bounds(&coords) {
+                    panic!("{:?}: {}", coords, e);
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn exhaustive_scaleup_implies_safety() {
+    let vs = [0.0, 0.5, 1.0];
+    for bits in 0u32..32 {
+        let flags = std::array::from_fn(|i| bits & (1 << i) != 0);
+        for &v_prev in &vs {
+            for &v_next in &vs {
+                for eps in [0.0, 0.1] {
+                    let case = GateCase {
+                        flags,
+                        v_prev,
+                        v_next,
+                        eps,
+                    };
+                    if let Err(e) = check_gates(&case) {
+                        panic!("{:?}: {}", case, e);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Every residual form a corridor spec can declare.
+fn all_forms() -> Vec<CorridorForm> {
+    let p = vec![
+        vec![0.4, 0.1, 0.0],
+        vec![0.1, 0.3, 0.0],
+        vec![0.0, 0.0, 0.3],
+    ];
+    vec![
+        CorridorForm::Linear,
+        CorridorForm::Quadratic(Quadratic::weighted()),
+        CorridorForm::Quadratic(Quadratic::new(p).unwrap()),
+        CorridorForm::MaxNorm,
+        CorridorForm::LogBarrier,
+    ]
+}
+
+#[test]
+fn exhaustive_hard_violation_is_not_admissible() {
+    let nox = [0.0, 80.0, 149.9, 150.0, 151.0];
+    let pm = [0.0, 5.0, 10.0, 12.0];
+    let o2 = [5.0, 6.0, 6.1, 9.0, 12.0];
+    for form in all_forms() {
+        let corridors = corridors().with_form(form.clone()).unwrap();
+        let mut violating = 0;
+        for &a in &nox {
+            for &b in &pm {
+                for &c in &o2 {
+                    for v_t in [0.0, 0.5, 1e9] {
+                        let values = [a, b, c];
+                        if fixture_r(&corridors, &values).iter().any(|r| *r >= 1.0) {
+                            violating += 1;
+                        }
+                        if let Err(e) = check_hard_violation(&corridors, &values, v_t) {
+                            panic!("{} {:?} at V_t={}: {}", form, values, v_t, e);
+                        }
+                    }
+                }
+            }
+        }
+        assert!(violating > 0, "grid never reaches r ≥ 1");
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Property-based generation
+// ---------------------------------------------------------------------------
+
+#[derive(Clone, Debug)]
+struct CoordCase {
+    direction: Direction,
+    r_min: f64,
+    span: f64,
+    w: f64,
+    x: f64,
+}
+
+fn gen_coord_case(rng: &mut SplitMix64) -> CoordCase {
+    let r_min = rng.range(-1e3, 1e3);
+    let span = 10f64.powf(rng.range(-6.0, 6.0));
+    CoordCase {
+        direction: if rng.next_u64() % 2 == 0 {
+            Direction::Max
+        } else {
+            Direction::Min
+        },
+        r_min,
+        span,
+        w: rng.range(0.0, 10.0),
+        x: r_min + span * rng.range(-3.0, 4.0),
+    }
+}
+
+fn shrink_coord_case(c: &CoordCase) -> Vec<CoordCase> {
+    let mut out = Vec::new();
+    for r_min in shrink_f64(c.r_min) {
+        out.push(CoordCase { r_min, ..c.clone() });
+    }
+    for span in shrink_f64(c.span).into_iter().filter(|s| *s > 0.0) {
+        out.push(CoordCase { span, ..c.clone() });
+    }
+    for w in shrink_f64(c.w).into_iter().filter(|w| *w >= 0.0) {
+        out.push(CoordCase { w, ..c.clone() });
+    }
+    for x in shrink_f64(c.x) {
+        out.push(CoordCase { x, ..c.clone() });
+    }
+    out
+}
+
+fn gen_coords(rng: &mut SplitMix64) -> Vec<RiskCoord> {
+    let n = 1 + (rng.next_u64() % 12) as usize;
+    (0..n)
+        .map(|_| RiskCoord {
+            r: rng.next_f64(),
+            w: rng.range(0.0, 5.0),
+        })
+        .collect()
+}
+
+fn shrink_coords(coords: &[RiskCoord]) -> Vec<Vec<RiskCoord>> {
+    shrink_vec(coords, |c| {
+        let mut out: Vec<RiskCoord> = shrink_f64(c.r)
+            .into_iter()
+            .filter(|r| (0.0..=1.0).contains(r))
+            .map(|r| RiskCoord { r, w: c.w })
+            .collect();
+        out.extend(
+            shrink_f64(c.w)
+                .into_iter()
+                .filter(|w| *w >= 0.0)
+                .map(|w| RiskCoord { r: c.r, w }),
+        );
+        out
+    })
+}
+
+#[test]
+fn property_r_in_unit_interval() {
+    forall(
+        "r ∈ [0,1]",
+        0x5eed_0001,
+        gen_coord_case,
+        shrink_coord_case,
+        |c| check_r_bounds(c.direction, c.r_min, c.r_min + c.span, c.w, c.x),
+    );
+}
+
+#[test]
+fn property_v_within_weight_sum() {
+    forall(
+        "V ∈ [0, Σw]",
+        0x5eed_0002,
+        gen_coords,
+        |c| shrink_coords(c),
+        |c| check_v_bounds(c),
+    );
+}
+
+#[test]
+fn property_scaleup_implies_safety() {
+    forall(
+        "scale-up ⇒ safety",
+        0x5eed_0003,
+        |rng| GateCase {
+            flags: std::array::from_fn(|_| rng.next_u64() % 4 != 0),
+            v_prev: rng.range(0.0, 2.0),
+            v_next: rng.range(0.0, 2.0),
+            eps: rng.range(0.0, 0.1),
+        },
+        |c| {
+            let mut out = Vec::new();
+            for i in 0..5 {
+                if c.flags[i] {
+                    let mut flags = c.flags;
+                    flags[i] = false;
+                    out.push(GateCase { flags, ..c.clone() });
+                }
+            }
+            for v_prev in shrink_f64(c.v_prev) {
+                out.push(GateCase {
+                    v_prev,
+                    ..c.clone()
+                });
+            }
+            for v_next in shrink_f64(c.v_next) {
+                out.push(GateCase {
+                    v_next,
+                    ..c.clone()
+                });
+            }
+            for eps in shrink_f64(c.eps) {
+                out.push(GateCase { eps, ..c.clone() });
+            }
+            out
+        },
+        check_gates,
+    );
+}
+
+#[test]
+fn property_hard_violation_is_not_admissible() {
+    let corridors = corridors();
+    forall(
+        "r ≥ 1 ⇒ not admissible",
+        0x5eed_0004,
+        |rng| {
+            (
+                [
+                    rng.range(0.0, 200.0),
+                    rng.range(0.0, 15.0),
+                    rng.range(3.0, 15.0),
+                ],
+                rng.range(0.0, 2.0),
+            )
+        },
+        |(values, v_t)| {
+            let mut out = Vec::new();
+            for i in 0..3 {
+                for x in shrink_f64(values[i]) {
+                    let mut next = *values;
+                    next[i] = x;
+                    out.push((next, *v_t));
+                }
+            }
+            for v in shrink_f64(*v_t) {
+                out.push((*values, v));
+            }
+            out
+        },
+        |(values, v_t)| check_hard_violation(&corridors, values, *v_t),
+    );
+}
+
+// ---------------------------------------------------------------------------
+// The harness itself must catch and minimise a broken implementation.
+// ---------------------------------------------------------------------------
+
+#[test]
+fn shrinking_minimises_a_broken_residual() {
+    // Mutant: forgets to multiply by r, so V = Σw and the bound check
+    // against a stricter Σ(r·w) oracle fails whenever some r < 1 has w > 0.
+    let broken = |coords: &[RiskCoord]| -> Result<(), String> {
+        let v: f64 = coords.iter().map(|c| c.w).sum();
+        let oracle: f64 = coords.iter().map(|c| c.r * c.w).sum();
+        if v > oracle {
+            return Err(format!("V = {} exceeds Σ r·w = {}", v, oracle));
+        }
+        Ok(())
+    };
+    let cx = find_counterexample(
+        7,
+        CASES,
+        gen_coords,
+        |c| shrink_coords(c),
+        |c: &Vec<RiskCoord>| broken(c),
+    )
+    .expect("mutant not detected");
+    assert_eq!(cx.shrunk.len(), 1, "not minimal: {:?}", cx.shrunk);
+    assert_eq!(cx.shrunk[0].r, 0.0, "not minimal: {:?}", cx.shrunk);
+    assert_eq!(cx.shrunk[0].w, 1.0, "not minimal: {:?}", cx.shrunk);
+}